@@ -0,0 +1,236 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::error::Error;
+use crate::game::action::Action;
+use crate::game::rules::GameRules;
+use crate::race::Race;
+use crate::search::{self, Budget};
+use crate::store::Store;
+
+#[derive(Debug)]
+pub struct BatchEntry {
+    pub map: String,
+    pub score: f64,
+    pub finished: bool,
+    pub turns: usize,
+    pub checkpoints: usize,
+    pub total_checkpoints: usize,
+    pub elapsed: Duration,
+    pub actions: Vec<Action>,
+}
+
+pub fn list_maps<P: AsRef<Path>>(dir: P) -> Result<Vec<PathBuf>, Error> {
+    let mut maps: Vec<PathBuf> = fs::read_dir(&dir)
+        .map_err(|source| Error::Io {
+            path: dir.as_ref().to_path_buf(),
+            source,
+        })?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+
+    // test2 comes before test10
+    maps.sort_by_key(|path| {
+        let name = path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let digits: String = name.chars().filter(|c| c.is_ascii_digit()).collect();
+        (digits.parse::<u64>().unwrap_or(u64::MAX), name)
+    });
+    Ok(maps)
+}
+
+pub fn solve_map<P: AsRef<Path>>(
+    path: P,
+    solver: &str,
+    seed: u64,
+    time_limit: Option<Duration>,
+    rules: GameRules,
+    store: Option<&Store>,
+) -> Result<BatchEntry, Error> {
+    let map = path
+        .as_ref()
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    // Solvers which refine a solution start from the stored best
+    let initial = match store {
+        Some(store) => store.actions(&map)?,
+        None => Vec::new(),
+    };
+    let mut search = search::by_name_from(solver, seed, initial)
+        .ok_or_else(|| Error::UnknownSolver(solver.to_string()))?;
+    let race = Race::load_with_rules(&path, rules)?;
+    let pod = race.initial_pod();
+    let mut budget = match time_limit {
+        Some(limit) => Budget::time(limit),
+        None => Budget::unlimited(),
+    };
+
+    let start = Instant::now();
+    let result = search.search(&race.track, &pod, &mut budget);
+    let elapsed = start.elapsed();
+
+    let end = race.simulate(&result.actions);
+    Ok(BatchEntry {
+        map,
+        score: result.score,
+        finished: result.finished,
+        turns: result.actions.len(),
+        checkpoints: end.next_checkpoint_id,
+        total_checkpoints: race.track.total(),
+        elapsed,
+        actions: result.actions,
+    })
+}
+
+pub fn run_batch<P: AsRef<Path>>(
+    dir: P,
+    solver: &str,
+    seed: u64,
+    time_limit: Option<Duration>,
+    rules: GameRules,
+    store: Option<&Store>,
+) -> Result<Vec<BatchEntry>, Error> {
+    list_maps(dir)?
+        .iter()
+        .map(|map| solve_map(map, solver, seed, time_limit, rules, store))
+        .collect()
+}
+
+pub fn scoreboard(entries: &[BatchEntry]) -> String {
+    let mut lines = vec![format!(
+        "{:<10} {:>10} {:>6} {:>12} {:>10}",
+        "map", "score", "turns", "checkpoints", "time (ms)"
+    )];
+
+    for entry in entries {
+        // An unfinished race has no real score
+        let score = if entry.finished {
+            format!("{:.2}", entry.score)
+        } else {
+            "DNF".to_string()
+        };
+        lines.push(format!(
+            "{:<10} {:>10} {:>6} {:>12} {:>10}",
+            entry.map,
+            score,
+            entry.turns,
+            format!("{}/{}", entry.checkpoints, entry.total_checkpoints),
+            entry.elapsed.as_millis()
+        ));
+    }
+
+    let finished: Vec<&BatchEntry> = entries.iter().filter(|entry| entry.finished).collect();
+    lines.push(format!(
+        "{:<10} {:>10.2} {:>6} {:>12} {:>10}",
+        "total",
+        finished.iter().map(|entry| entry.score).sum::<f64>(),
+        entries.iter().map(|entry| entry.turns).sum::<usize>(),
+        format!("{}/{} done", finished.len(), entries.len()),
+        entries
+            .iter()
+            .map(|entry| entry.elapsed.as_millis())
+            .sum::<u128>()
+    ));
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_maps_natural_order() {
+        let maps = list_maps("testcases").unwrap();
+        let names: Vec<String> = maps
+            .iter()
+            .map(|path| path.file_stem().unwrap().to_string_lossy().to_string())
+            .collect();
+
+        assert_eq!(names[0], "test1");
+        assert_eq!(names[1], "test2");
+        assert_eq!(names[2], "test7");
+        assert_eq!(names.last().unwrap(), "test704");
+    }
+
+    #[test]
+    fn test_solve_map() {
+        let entry = solve_map(
+            "testcases/test1.json",
+            "greedy",
+            0,
+            None,
+            GameRules::default(),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(entry.map, "test1");
+        assert!(entry.finished);
+        assert_eq!(entry.checkpoints, entry.total_checkpoints);
+        assert!(matches!(
+            solve_map(
+                "testcases/test1.json",
+                "unknown",
+                0,
+                None,
+                GameRules::default(),
+                None
+            ),
+            Err(Error::UnknownSolver(_))
+        ));
+        assert!(matches!(
+            solve_map(
+                "testcases/missing.json",
+                "greedy",
+                0,
+                None,
+                GameRules::default(),
+                None
+            ),
+            Err(Error::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn test_scoreboard() {
+        let entries = vec![
+            BatchEntry {
+                map: "test1".to_string(),
+                score: 100.5,
+                finished: true,
+                turns: 101,
+                checkpoints: 9,
+                total_checkpoints: 9,
+                elapsed: Duration::from_millis(12),
+                actions: Vec::new(),
+            },
+            BatchEntry {
+                map: "test2".to_string(),
+                score: 50.0,
+                finished: false,
+                turns: 600,
+                checkpoints: 4,
+                total_checkpoints: 9,
+                elapsed: Duration::from_millis(30),
+                actions: Vec::new(),
+            },
+        ];
+
+        let board = scoreboard(&entries);
+        let lines: Vec<&str> = board.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].contains("100.50"));
+        assert!(lines[2].contains("DNF"));
+        assert!(lines[3].starts_with("total"));
+        assert!(lines[3].contains("1/2 done"));
+        assert!(lines[3].contains("701"));
+    }
+}
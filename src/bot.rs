@@ -0,0 +1,404 @@
+use std::io::{self, BufRead, Write};
+
+use crate::game::checkpoint::CheckPoint;
+use crate::game::pod::Pod;
+use crate::game::rules::GameRules;
+use crate::game::track::Track;
+use crate::search::{Budget, Planner};
+
+#[derive(Debug, PartialEq)]
+pub struct TurnInput {
+    pub checkpoint_index: usize,
+    pub x: f64,
+    pub y: f64,
+    pub vx: f64,
+    pub vy: f64,
+    pub angle: f64,
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn read_values<R: BufRead>(input: &mut R) -> io::Result<Option<Vec<i32>>> {
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+
+    line.split_whitespace()
+        .map(|token| {
+            token.parse::<i32>().map_err(|_| {
+                invalid_data(format!("invalid number '{}' in '{}'", token, line.trim()))
+            })
+        })
+        .collect::<io::Result<Vec<i32>>>()
+        .map(Some)
+}
+
+pub fn read_checkpoints<R: BufRead>(input: &mut R) -> io::Result<Vec<CheckPoint>> {
+    let count = match read_values(input)? {
+        Some(values) if values.len() == 1 => values[0],
+        _ => return Err(invalid_data("missing checkpoint count".to_string())),
+    };
+    // The start line and at least one checkpoint to reach
+    if count < 2 {
+        return Err(invalid_data(format!("invalid checkpoint count {}", count)));
+    }
+
+    // The count is not trusted for the allocation, the lines have to be there
+    let mut checkpoints = Vec::new();
+    for _ in 0..count {
+        match read_values(input)? {
+            Some(values) if values.len() == 2 => {
+                checkpoints.push(CheckPoint::from_i32(values[0], values[1]))
+            }
+            _ => {
+                return Err(invalid_data(
+                    "expected 'checkpointX checkpointY'".to_string(),
+                ));
+            }
+        }
+    }
+    Ok(checkpoints)
+}
+
+pub fn read_turn<R: BufRead>(input: &mut R) -> io::Result<Option<TurnInput>> {
+    match read_values(input)? {
+        None => Ok(None),
+        Some(values) if values.len() == 6 => Ok(Some(TurnInput {
+            checkpoint_index: values[0] as usize,
+            x: values[1] as f64,
+            y: values[2] as f64,
+            vx: values[3] as f64,
+            vy: values[4] as f64,
+            angle: values[5] as f64,
+        })),
+        Some(_) => Err(invalid_data(
+            "expected 'checkpointIndex x y vx vy angle'".to_string(),
+        )),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Desync {
+    pub field: &'static str,
+    pub predicted: f64,
+    pub reported: f64,
+}
+
+impl std::fmt::Display for Desync {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: predicted {} reported {}",
+            self.field, self.predicted, self.reported
+        )
+    }
+}
+
+pub fn compare(predicted: &Pod, reported: &Pod) -> Vec<Desync> {
+    let fields = [
+        ("x", predicted.x, reported.x),
+        ("y", predicted.y, reported.y),
+        ("vx", predicted.vx, reported.vx),
+        ("vy", predicted.vy, reported.vy),
+        ("angle", predicted.angle, reported.angle),
+        (
+            "next_checkpoint_id",
+            predicted.next_checkpoint_id as f64,
+            reported.next_checkpoint_id as f64,
+        ),
+    ];
+
+    fields
+        .into_iter()
+        .filter(|(_, predicted, reported)| predicted != reported)
+        .map(|(field, predicted, reported)| Desync {
+            field,
+            predicted,
+            reported,
+        })
+        .collect()
+}
+
+pub struct Bot<P: Planner> {
+    pub planner: P,
+    pub track: Track,
+    pub pod: Option<Pod>,
+    // State expected for the next turn, after the last action was played
+    pub predicted: Option<Pod>,
+    pub desyncs: Vec<(usize, Desync)>,
+    last_index: Option<usize>,
+}
+
+impl<P: Planner> Bot<P> {
+    pub fn new(planner: P, map: &[CheckPoint], rules: GameRules) -> Self {
+        Bot {
+            planner,
+            track: Track::from_map(map, rules),
+            pod: None,
+            predicted: None,
+            desyncs: Vec::new(),
+            last_index: None,
+        }
+    }
+
+    pub fn update(&mut self, turn: &TurnInput) -> &Pod {
+        // The referee only gives the index in the map, the lap is deduced from the crossings:
+        // every change of index is one more checkpoint crossed
+        let (next_checkpoint_id, turn_count) = match &self.pod {
+            Some(pod) => {
+                let crossed = self
+                    .last_index
+                    .is_some_and(|last| last != turn.checkpoint_index);
+                (pod.next_checkpoint_id + crossed as usize, pod.turn + 1)
+            }
+            None => (0, 0),
+        };
+        self.last_index = Some(turn.checkpoint_index);
+
+        let mut pod = Pod::new(
+            turn.x,
+            turn.y,
+            turn.vx,
+            turn.vy,
+            turn.angle,
+            next_checkpoint_id,
+        );
+        pod.turn = turn_count;
+
+        // The reported state always wins, the prediction is only checked against it
+        if let Some(predicted) = self.predicted.take() {
+            for desync in compare(&predicted, &pod) {
+                eprintln!("Desync on turn {}: {}", pod.turn, desync);
+                self.desyncs.push((pod.turn, desync));
+            }
+        }
+
+        self.pod = Some(pod);
+        self.pod.as_ref().unwrap()
+    }
+
+    pub fn play_turn(&mut self, turn: &TurnInput, budget: &mut Budget) -> String {
+        self.update(turn);
+        let pod = self.pod.as_ref().unwrap();
+
+        let action = self.planner.next_action(&self.track, pod, budget);
+        let command = match pod.check_command(&action, &self.track) {
+            Ok(command) => command,
+            Err(mismatch) => {
+                eprintln!("Turn {}: {}", pod.turn, mismatch);
+                mismatch.command
+            }
+        };
+
+        // The prediction follows what the referee will do with the integer target
+        let mut predicted = pod.clone_pod();
+        predicted.apply_command(&command, &self.track);
+        self.predicted = Some(predicted);
+
+        command.to_string()
+    }
+}
+
+pub fn fingerprint(map: &[CheckPoint]) -> u64 {
+    // FNV-1a over the coordinates, enough to recognize a map from its startup input
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for checkpoint in map {
+        for value in [checkpoint.x as i32, checkpoint.y as i32] {
+            for byte in value.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        }
+    }
+    hash
+}
+
+pub fn run<R: BufRead, W: Write, P: Planner>(
+    input: &mut R,
+    output: &mut W,
+    planner: P,
+    rules: GameRules,
+) -> io::Result<()> {
+    let map = read_checkpoints(input)?;
+    play(input, output, &map, planner, rules)
+}
+
+pub fn play<R: BufRead, W: Write, P: Planner>(
+    input: &mut R,
+    output: &mut W,
+    map: &[CheckPoint],
+    planner: P,
+    rules: GameRules,
+) -> io::Result<()> {
+    let mut bot = Bot::new(planner, map, rules);
+
+    let mut first_turn = true;
+    while let Some(turn) = read_turn(input)? {
+        let mut budget = if first_turn {
+            Budget::first_turn()
+        } else {
+            Budget::turn()
+        };
+        first_turn = false;
+
+        let command = bot.play_turn(&turn, &mut budget);
+        writeln!(output, "{}", command)?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::GreedySearch;
+
+    #[test]
+    fn test_read_inputs() {
+        let mut input = "3\n10353 1986\n2757 4659\n3358 2838\n1 10353 1986 0 0 161\n".as_bytes();
+
+        let map = read_checkpoints(&mut input).unwrap();
+        assert_eq!(map.len(), 3);
+        assert_eq!(map[1], CheckPoint::from_i32(2757, 4659));
+
+        let turn = read_turn(&mut input).unwrap().unwrap();
+        assert_eq!(turn.checkpoint_index, 1);
+        assert_eq!(turn.x, 10353.0);
+        assert_eq!(turn.angle, 161.0);
+
+        assert!(read_turn(&mut input).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let mut input = "2\n0 0\n100 abc\n".as_bytes();
+        assert!(read_checkpoints(&mut input).is_err());
+    }
+
+    #[test]
+    fn test_invalid_checkpoint_count() {
+        for count in ["-1", "0", "1"] {
+            let input = format!("{}\n0 0\n", count);
+            let error = read_checkpoints(&mut input.as_bytes()).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidData, "{}", count);
+        }
+    }
+
+    #[test]
+    fn test_lap_tracking() {
+        let map = vec![
+            CheckPoint::from_i32(0, 0),
+            CheckPoint::from_i32(5000, 0),
+            CheckPoint::from_i32(5000, 5000),
+        ];
+        let mut bot = Bot::new(GreedySearch::default(), &map, GameRules::default());
+        let mut turn = TurnInput {
+            checkpoint_index: 1,
+            x: 0.0,
+            y: 0.0,
+            vx: 0.0,
+            vy: 0.0,
+            angle: 0.0,
+        };
+
+        assert_eq!(bot.update(&turn).next_checkpoint_id, 0);
+        assert_eq!(bot.update(&turn).turn, 1);
+        turn.checkpoint_index = 2;
+        assert_eq!(bot.update(&turn).next_checkpoint_id, 1);
+        turn.checkpoint_index = 0;
+        assert_eq!(bot.update(&turn).next_checkpoint_id, 2);
+        turn.checkpoint_index = 1;
+        assert_eq!(bot.update(&turn).next_checkpoint_id, 3);
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let map = crate::race::load_points("testcases/test1.json").unwrap();
+        let mut input = "3\n10353 1986\n2757 4659\n3358 2838\n".as_bytes();
+
+        assert_eq!(
+            fingerprint(&read_checkpoints(&mut input).unwrap()),
+            fingerprint(&map)
+        );
+        assert_ne!(fingerprint(&map), fingerprint(&map[1..]));
+        assert_ne!(
+            fingerprint(&map),
+            fingerprint(&crate::race::load_points("testcases/test2.json").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_compare() {
+        let predicted = Pod::new(100.0, 200.0, 10.0, -5.0, 90.0, 2);
+        let reported = Pod::new(100.0, 201.0, 10.0, -5.0, 91.0, 2);
+
+        let desyncs = compare(&predicted, &reported);
+
+        assert_eq!(desyncs.len(), 2);
+        assert_eq!(desyncs[0].field, "y");
+        assert_eq!(desyncs[1].to_string(), "angle: predicted 90 reported 91");
+        assert!(compare(&predicted, &predicted).is_empty());
+    }
+
+    #[test]
+    fn test_desync_detection() {
+        let map = vec![
+            CheckPoint::from_i32(0, 0),
+            CheckPoint::from_i32(5000, 0),
+            CheckPoint::from_i32(5000, 5000),
+        ];
+        let mut bot = Bot::new(GreedySearch::default(), &map, GameRules::default());
+        let turn = TurnInput {
+            checkpoint_index: 1,
+            x: 0.0,
+            y: 0.0,
+            vx: 0.0,
+            vy: 0.0,
+            angle: 0.0,
+        };
+        bot.play_turn(&turn, &mut Budget::unlimited());
+
+        // Reporting exactly what was predicted is silent
+        let predicted = bot.predicted.as_ref().unwrap();
+        let next = TurnInput {
+            checkpoint_index: 1,
+            x: predicted.x,
+            y: predicted.y,
+            vx: predicted.vx,
+            vy: predicted.vy,
+            angle: predicted.angle,
+        };
+        bot.play_turn(&next, &mut Budget::unlimited());
+        assert!(bot.desyncs.is_empty());
+
+        // A pod which did not move is reported and the bot resyncs on it
+        bot.update(&next);
+        assert!(!bot.desyncs.is_empty());
+        assert!(bot.desyncs.iter().all(|(turn, _)| *turn == 2));
+        assert_eq!(bot.pod.as_ref().unwrap().x, next.x);
+    }
+
+    #[test]
+    fn test_run_outputs_one_command_per_turn() {
+        let mut input =
+            "3\n10353 1986\n2757 4659\n3358 2838\n1 10353 1986 0 0 161\n1 10192 2041 -136 47 161\n"
+                .as_bytes();
+        let mut output = Vec::new();
+
+        run(
+            &mut input,
+            &mut output,
+            GreedySearch::default(),
+            GameRules::default(),
+        )
+        .unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].split(' ').count(), 3);
+    }
+}
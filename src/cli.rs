@@ -0,0 +1,259 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use cg_search_race_rust::game::GameRules;
+
+pub const USAGE: &str = "Usage: cg_search_race_rust <command> [options]
+
+Commands:
+  solve <map>              Solve one testcase
+  batch [dir]              Solve every testcase of a directory (default: testcases)
+  verify <map> <actions>   Replay an action string, or a file containing one
+  bot                      Play on stdin/stdout with the CodinGame protocol
+  render <map> [actions]   Draw the map, and the trajectory of the actions, as SVG
+  submit [dir]             Write a CodinGame submission embedding the stored solutions
+
+Options:
+  -a, --algo <name>        Search algorithm (default: cem)
+  -t, --time <ms>          Time budget per map, not for bot (default: the iteration budget)
+  -s, --seed <n>           Seed of the random generators (default: 0)
+  -o, --output <path>      Write the result to a file instead of stdout
+  -f, --format <format>    text or json (default: text)
+      --store <dir>        Keep the best solution of each map in this directory
+      --rules <name>       search-race, coders-strike-back or one-lap (default: search-race)";
+
+#[derive(Debug, PartialEq)]
+pub enum Subcommand {
+    Solve {
+        map: PathBuf,
+    },
+    Batch {
+        dir: PathBuf,
+    },
+    Verify {
+        map: PathBuf,
+        actions: String,
+    },
+    Bot,
+    Render {
+        map: PathBuf,
+        actions: Option<String>,
+    },
+    Submit {
+        dir: PathBuf,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    pub algorithm: String,
+    pub time: Option<Duration>,
+    pub seed: u64,
+    pub output: Option<PathBuf>,
+    pub format: Format,
+    pub store: Option<PathBuf>,
+    pub rules: GameRules,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            algorithm: "cem".to_string(),
+            time: None,
+            seed: 0,
+            output: None,
+            format: Format::Text,
+            store: None,
+            rules: GameRules::default(),
+        }
+    }
+}
+
+pub fn parse(args: &[String]) -> Result<(Subcommand, Options), String> {
+    let mut options = Options::default();
+    let mut positional: Vec<&str> = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = |name: &str| {
+            iter.next()
+                .map(String::as_str)
+                .ok_or(format!("missing value for {}", name))
+        };
+
+        match arg.as_str() {
+            "-a" | "--algo" => options.algorithm = value(arg)?.to_string(),
+            "-t" | "--time" => {
+                let ms = value(arg)?;
+                let ms = ms
+                    .parse::<u64>()
+                    .map_err(|_| format!("invalid time '{}'", ms))?;
+                options.time = Some(Duration::from_millis(ms));
+            }
+            "-s" | "--seed" => {
+                let seed = value(arg)?;
+                options.seed = seed
+                    .parse::<u64>()
+                    .map_err(|_| format!("invalid seed '{}'", seed))?;
+            }
+            "-o" | "--output" => options.output = Some(PathBuf::from(value(arg)?)),
+            "--store" => options.store = Some(PathBuf::from(value(arg)?)),
+            "--rules" => {
+                let name = value(arg)?;
+                options.rules =
+                    GameRules::by_name(name).ok_or(format!("unknown rules '{}'", name))?;
+            }
+            "-f" | "--format" => {
+                options.format = match value(arg)? {
+                    "text" => Format::Text,
+                    "json" => Format::Json,
+                    other => return Err(format!("unknown format '{}'", other)),
+                }
+            }
+            other if other.starts_with('-') => return Err(format!("unknown option '{}'", other)),
+            other => positional.push(other),
+        }
+    }
+
+    let subcommand = match positional[..] {
+        ["solve", map] => Subcommand::Solve {
+            map: PathBuf::from(map),
+        },
+        ["batch"] => Subcommand::Batch {
+            dir: PathBuf::from("testcases"),
+        },
+        ["batch", dir] => Subcommand::Batch {
+            dir: PathBuf::from(dir),
+        },
+        ["verify", map, actions] => Subcommand::Verify {
+            map: PathBuf::from(map),
+            actions: actions.to_string(),
+        },
+        ["bot"] => Subcommand::Bot,
+        ["render", map] => Subcommand::Render {
+            map: PathBuf::from(map),
+            actions: None,
+        },
+        ["render", map, actions] => Subcommand::Render {
+            map: PathBuf::from(map),
+            actions: Some(actions.to_string()),
+        },
+        ["submit"] => Subcommand::Submit {
+            dir: PathBuf::from("testcases"),
+        },
+        ["submit", dir] => Subcommand::Submit {
+            dir: PathBuf::from(dir),
+        },
+        [] => return Err("missing command".to_string()),
+        [command, ..] => return Err(format!("invalid arguments for '{}'", command)),
+    };
+
+    // The submission always plays the Search Race, its solutions come from the store
+    if options.rules != GameRules::default() && matches!(subcommand, Subcommand::Submit { .. }) {
+        return Err("submit only plays the search-race rules".to_string());
+    }
+
+    // The bot plays against the CodinGame time limits of each turn
+    if options.time.is_some() && subcommand == Subcommand::Bot {
+        return Err("bot follows the time limits of the game, not --time".to_string());
+    }
+
+    // Checked before any map is solved, the store would refuse every solution
+    if options.store.is_some()
+        && options.rules != GameRules::default()
+        && matches!(
+            subcommand,
+            Subcommand::Solve { .. } | Subcommand::Batch { .. }
+        )
+    {
+        return Err("--store only keeps solutions under the search-race rules".to_string());
+    }
+
+    Ok((subcommand, options))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_subcommands() {
+        let (subcommand, options) = parse(&args("solve testcases/test1.json")).unwrap();
+        assert_eq!(
+            subcommand,
+            Subcommand::Solve {
+                map: PathBuf::from("testcases/test1.json")
+            }
+        );
+        assert_eq!(options, Options::default());
+
+        let (subcommand, _) = parse(&args("batch")).unwrap();
+        assert_eq!(
+            subcommand,
+            Subcommand::Batch {
+                dir: PathBuf::from("testcases")
+            }
+        );
+
+        let (subcommand, _) = parse(&args("verify map.json 200,0;100,18")).unwrap();
+        assert_eq!(
+            subcommand,
+            Subcommand::Verify {
+                map: PathBuf::from("map.json"),
+                actions: "200,0;100,18".to_string()
+            }
+        );
+
+        assert_eq!(parse(&args("bot")).unwrap().0, Subcommand::Bot);
+
+        let (subcommand, _) = parse(&args("submit --store best")).unwrap();
+        assert_eq!(
+            subcommand,
+            Subcommand::Submit {
+                dir: PathBuf::from("testcases")
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_options() {
+        let (_, options) = parse(&args(
+            "batch dir --algo beam -t 500 --seed 42 -o out.txt -f json --store best",
+        ))
+        .unwrap();
+
+        assert_eq!(options.algorithm, "beam");
+        assert_eq!(options.time, Some(Duration::from_millis(500)));
+        assert_eq!(options.seed, 42);
+        assert_eq!(options.output, Some(PathBuf::from("out.txt")));
+        assert_eq!(options.format, Format::Json);
+        assert_eq!(options.store, Some(PathBuf::from("best")));
+
+        let (_, options) = parse(&args("solve map --rules one-lap")).unwrap();
+        assert_eq!(options.rules, GameRules::one_lap());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse(&args("")).is_err());
+        assert!(parse(&args("solve")).is_err());
+        assert!(parse(&args("solve map --time")).is_err());
+        assert!(parse(&args("solve map --time abc")).is_err());
+        assert!(parse(&args("solve map --format xml")).is_err());
+        assert!(parse(&args("solve map --verbose")).is_err());
+        assert!(parse(&args("solve map --rules formula-one")).is_err());
+        assert!(parse(&args("batch --rules one-lap --store best")).is_err());
+        assert!(parse(&args("submit --rules one-lap --store best")).is_err());
+        assert!(parse(&args("bot --time 40")).is_err());
+    }
+}
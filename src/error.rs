@@ -0,0 +1,92 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+// A token that does not parse. The index locates it in a list: the action of a solution or
+// the point of a map.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub field: &'static str,
+    pub token: String,
+    pub index: Option<usize>,
+}
+
+impl ParseError {
+    pub fn new(field: &'static str, token: &str) -> Self {
+        ParseError {
+            field,
+            token: token.to_string(),
+            index: None,
+        }
+    }
+
+    pub fn at(mut self, index: usize) -> Self {
+        self.index = Some(index);
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid {} '{}'", self.field, self.token)?;
+        if let Some(index) = self.index {
+            write!(f, " at position {}", index)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug)]
+pub enum Error {
+    Io { path: PathBuf, source: io::Error },
+    // serde_json stays out of this module, its errors are kept as text
+    Json { path: PathBuf, message: String },
+    Parse { path: PathBuf, source: ParseError },
+    UnknownSolver(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            Error::Json { path, message } => {
+                write!(f, "{}: invalid JSON, {}", path.display(), message)
+            }
+            Error::Parse { path, source } => write!(f, "{}: {}", path.display(), source),
+            Error::UnknownSolver(name) => write!(f, "unknown solver '{}'", name),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Parse { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let error = ParseError::new("thrust", "2O0").at(3);
+        assert_eq!(error.to_string(), "invalid thrust '2O0' at position 3");
+
+        let error = Error::Parse {
+            path: PathBuf::from("best/test1.json"),
+            source: error,
+        };
+        assert_eq!(
+            error.to_string(),
+            "best/test1.json: invalid thrust '2O0' at position 3"
+        );
+        assert!(std::error::Error::source(&error).is_some());
+    }
+}
@@ -1,4 +1,9 @@
-#[derive(Debug)]
+use std::str::FromStr;
+
+use crate::error::ParseError;
+use crate::game::rules::GameRules;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Action {
     pub thrust: i32,
     pub angle: i32,
@@ -9,11 +14,9 @@ impl Action {
         Self { thrust, angle }
     }
 
-    pub fn clone(&self) -> Self {
-        Self {
-            thrust: self.thrust,
-            angle: self.angle,
-        }
+    // Within the Search Race bounds, see GameRules::allows for the other variants
+    pub fn is_valid(&self) -> bool {
+        GameRules::default().allows(self)
     }
 }
 
@@ -23,11 +26,74 @@ impl std::fmt::Display for Action {
     }
 }
 
-impl From<&str> for Action {
-    fn from(s: &str) -> Self {
-        let mut parts = s.split(',');
-        let thrust = parts.next().unwrap_or("0").parse::<i32>().unwrap_or(0);
-        let angle = parts.next().unwrap_or("0").parse::<i32>().unwrap_or(0);
-        Action::new(thrust, angle)
+impl FromStr for Action {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (thrust, angle) = s
+            .trim()
+            .split_once(',')
+            .ok_or(ParseError::new("action", s.trim()))?;
+
+        let thrust = thrust
+            .trim()
+            .parse::<i32>()
+            .map_err(|_| ParseError::new("thrust", thrust.trim()))?;
+        let angle = angle
+            .trim()
+            .parse::<i32>()
+            .map_err(|_| ParseError::new("angle", angle.trim()))?;
+        Ok(Action::new(thrust, angle))
+    }
+}
+
+impl TryFrom<&str> for Action {
+    type Error = ParseError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+pub fn parse_actions(s: &str) -> Result<Vec<Action>, ParseError> {
+    // "thrust,angle" separated by ';', a trailing ';' is accepted
+    s.trim()
+        .split(';')
+        .filter(|action| !action.trim().is_empty())
+        .enumerate()
+        .map(|(i, action)| action.parse::<Action>().map_err(|e| e.at(i)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_action() {
+        assert_eq!("200,-18".parse(), Ok(Action::new(200, -18)));
+        assert_eq!(Action::try_from(" 50 , 3 "), Ok(Action::new(50, 3)));
+        assert_eq!(
+            "2O0,0".parse::<Action>(),
+            Err(ParseError::new("thrust", "2O0"))
+        );
+        assert_eq!("200,".parse::<Action>(), Err(ParseError::new("angle", "")));
+        assert_eq!(
+            "200".parse::<Action>(),
+            Err(ParseError::new("action", "200"))
+        );
+    }
+
+    #[test]
+    fn test_parse_actions() {
+        assert_eq!(
+            parse_actions("200,0;100,18;"),
+            Ok(vec![Action::new(200, 0), Action::new(100, 18)])
+        );
+        assert_eq!(parse_actions(""), Ok(Vec::new()));
+        assert_eq!(
+            parse_actions("200,0;100,x;0,0"),
+            Err(ParseError::new("angle", "x").at(1))
+        );
     }
 }
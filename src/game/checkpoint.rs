@@ -27,6 +27,12 @@ impl CheckPoint {
         }
     }
 
+    pub fn with_radius(mut self, r: f64) -> Self {
+        self.r = r;
+        self.r2 = r * r;
+        self
+    }
+
     pub fn clone_checkpoint(&self) -> Self {
         CheckPoint {
             x: self.x,
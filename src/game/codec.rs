@@ -0,0 +1,129 @@
+use crate::error::ParseError;
+use crate::game::action::Action;
+
+// Packed form of an action list: each run of identical actions is 18 bits, 8 bits of thrust,
+// 6 bits of angle (shifted by 18) and 4 bits of repeat count, written as 3 base64 characters
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const MAX_RUN: usize = 16;
+
+fn digit(c: u8) -> Option<u32> {
+    ALPHABET.iter().position(|&a| a == c).map(|i| i as u32)
+}
+
+pub fn encode(actions: &[Action]) -> Result<String, ParseError> {
+    let mut encoded = String::with_capacity(actions.len() * 3);
+
+    let mut i = 0;
+    while i < actions.len() {
+        let action = actions[i];
+        if !action.is_valid() {
+            return Err(ParseError::new("packable action", &action.to_string()).at(i));
+        }
+
+        let mut run = 1;
+        while run < MAX_RUN && i + run < actions.len() && actions[i + run] == action {
+            run += 1;
+        }
+
+        let bits =
+            ((action.thrust as u32) << 10) | (((action.angle + 18) as u32) << 4) | (run as u32 - 1);
+        for shift in [12, 6, 0] {
+            encoded.push(ALPHABET[((bits >> shift) & 63) as usize] as char);
+        }
+        i += run;
+    }
+
+    Ok(encoded)
+}
+
+pub fn decode(s: &str) -> Result<Vec<Action>, ParseError> {
+    let s = s.trim();
+    if !s.is_ascii() || !s.len().is_multiple_of(3) {
+        return Err(ParseError::new("packed actions", s));
+    }
+
+    let mut actions = Vec::with_capacity(s.len());
+    for (i, chunk) in s.as_bytes().chunks(3).enumerate() {
+        let token = &s[i * 3..i * 3 + 3];
+        let mut bits = 0;
+        for &c in chunk {
+            let value = digit(c).ok_or_else(|| ParseError::new("packed run", token).at(i))?;
+            bits = (bits << 6) | value;
+        }
+
+        let action = Action::new((bits >> 10) as i32, ((bits >> 4) & 63) as i32 - 18);
+        if !action.is_valid() {
+            return Err(ParseError::new("packed run", token).at(i));
+        }
+        let run = (bits & 15) as usize + 1;
+        actions.extend(std::iter::repeat_n(action, run));
+    }
+
+    Ok(actions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::rng::Rng;
+
+    #[test]
+    fn test_round_trip_extremes() {
+        let actions = vec![
+            Action::new(0, -18),
+            Action::new(200, 18),
+            Action::new(0, 18),
+            Action::new(200, -18),
+            Action::new(100, 0),
+        ];
+
+        let encoded = encode(&actions).unwrap();
+        assert_eq!(encoded.len(), 15);
+        assert_eq!(decode(&encoded).unwrap(), actions);
+    }
+
+    #[test]
+    fn test_round_trip_random() {
+        let mut rng = Rng::new(7);
+        for _ in 0..100 {
+            let len = rng.below(300);
+            let actions: Vec<Action> = (0..len)
+                .map(|_| {
+                    // Few distinct values so that runs show up
+                    Action::new(rng.range(0, 4) * 50, rng.range(-1, 1) * 18)
+                })
+                .collect();
+
+            assert_eq!(decode(&encode(&actions).unwrap()).unwrap(), actions);
+        }
+    }
+
+    #[test]
+    fn test_runs() {
+        assert_eq!(encode(&[]).unwrap(), "");
+        assert!(decode("").unwrap().is_empty());
+
+        // 16 repeats fit in one run, the 17th starts a new one
+        let actions = vec![Action::new(200, 0); 17];
+        let encoded = encode(&actions).unwrap();
+        assert_eq!(encoded.len(), 6);
+        assert_eq!(decode(&encoded).unwrap(), actions);
+        assert_eq!(encode(&actions[..16]).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert_eq!(
+            encode(&[Action::new(200, 0), Action::new(201, 0)]),
+            Err(ParseError::new("packable action", "201,0").at(1))
+        );
+        assert!(encode(&[Action::new(100, -19)]).is_err());
+        assert!(decode("AB").is_err());
+        assert!(decode("AB,").is_err());
+        // Thrust 255 does not exist
+        assert_eq!(
+            decode("///"),
+            Err(ParseError::new("packed run", "///").at(0))
+        );
+    }
+}
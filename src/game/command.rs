@@ -0,0 +1,179 @@
+use crate::game::action::Action;
+use crate::game::pod::Pod;
+use crate::game::track::Track;
+
+// What is actually sent to the referee: an integer target point and a thrust
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Command {
+    pub x: i32,
+    pub y: i32,
+    pub thrust: i32,
+}
+
+impl Command {
+    pub fn new(x: i32, y: i32, thrust: i32) -> Self {
+        Self { x, y, thrust }
+    }
+}
+
+impl std::fmt::Display for Command {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.x, self.y, self.thrust)
+    }
+}
+
+impl std::str::FromStr for Command {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Anything after the thrust is the debug message shown by CodinGame
+        let values: Vec<i32> = s
+            .split_whitespace()
+            .take(3)
+            .map(|token| {
+                token
+                    .parse::<i32>()
+                    .map_err(|_| format!("invalid number '{}' in '{}'", token, s.trim()))
+            })
+            .collect::<Result<Vec<i32>, String>>()?;
+
+        match values[..] {
+            [x, y, thrust] => Ok(Command::new(x, y, thrust)),
+            _ => Err(format!("expected 'X Y THRUST', got '{}'", s.trim())),
+        }
+    }
+}
+
+pub fn parse_log(log: &str) -> Result<Vec<Command>, String> {
+    // One command per line (or separated by ';'), empty lines are ignored
+    log.split(['\n', ';'])
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .enumerate()
+        .map(|(i, line)| {
+            line.parse::<Command>()
+                .map_err(|e| format!("command {}: {}", i + 1, e))
+        })
+        .collect()
+}
+
+pub fn to_actions(pod: &Pod, commands: &[Command], track: &Track) -> Vec<Action> {
+    // Each target is converted from the state the previous actions lead to. Fractional rotations
+    // are rounded, so the actions may drift from the original game but always replay to the
+    // score they are stored with.
+    let mut pod = pod.clone_pod();
+    let mut actions = Vec::with_capacity(commands.len());
+    for command in commands {
+        if pod.done {
+            break;
+        }
+        let rotation = pod.rotation_to(command, &track.rules);
+        let action = Action::new(command.thrust, rotation.round() as i32);
+        pod.apply_move(&action, track);
+        actions.push(action);
+    }
+    actions
+}
+
+// Reported when the command emitted for an action does not lead to the state planned with it
+#[derive(Debug, PartialEq)]
+pub struct CommandMismatch {
+    pub action: Action,
+    pub command: Command,
+    pub rotation: f64,
+}
+
+impl std::fmt::Display for CommandMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "action {} sent as '{}' rotates by {} degrees",
+            self.action, self.command, self.rotation
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::checkpoint::CheckPoint;
+    use crate::search::rng::Rng;
+
+    #[test]
+    fn test_parse_log() {
+        let commands = parse_log("100 200 150\n-5 7 0 hello\n\n8 9 200;1 2 3").unwrap();
+
+        assert_eq!(
+            commands,
+            vec![
+                Command::new(100, 200, 150),
+                Command::new(-5, 7, 0),
+                Command::new(8, 9, 200),
+                Command::new(1, 2, 3),
+            ]
+        );
+        assert_eq!(commands[0].to_string(), "100 200 150");
+    }
+
+    #[test]
+    fn test_parse_log_errors() {
+        assert!(parse_log("100 200").unwrap_err().contains("command 1"));
+        assert!(parse_log("1 2 3\n1 x 3").unwrap_err().contains("'x'"));
+    }
+
+    #[test]
+    fn test_to_actions() {
+        let track = Track::new(vec![CheckPoint::from_i32(8000, 3000)], 1);
+        let start = Pod::new(0.0, 0.0, 0.0, 0.0, 0.0, 0);
+        let actions = vec![
+            Action::new(200, 18),
+            Action::new(150, -7),
+            Action::new(0, 3),
+            Action::new(80, 0),
+        ];
+
+        let mut pod = start.clone_pod();
+        let mut commands = Vec::new();
+        for action in actions.iter() {
+            commands.push(pod.command(action));
+            pod.apply_move(action, &track);
+        }
+
+        assert_eq!(to_actions(&start, &commands, &track), actions);
+    }
+
+    #[test]
+    fn test_to_actions_follows_rounded_state() {
+        let track = Track::new(vec![CheckPoint::from_i32(-50000, -50000)], 1);
+        let start = Pod::new(0.0, 0.0, 0.0, 0.0, 0.0, 0);
+        // Far targets a few degrees around the heading: the turns are small and never whole
+        let mut rng = Rng::new(3);
+        let commands: Vec<Command> = (0..300)
+            .map(|_| Command::new(1_000_000, rng.range(-100_000, 100_000), rng.range(0, 200)))
+            .collect();
+
+        let actions = to_actions(&start, &commands, &track);
+
+        // Every angle is the rounded turn towards the target from the state the actions reach
+        let mut pod = start.clone_pod();
+        let mut fractional = false;
+        for (command, action) in commands.iter().zip(actions.iter()) {
+            let rotation = pod.rotation_to(command, &track.rules);
+            fractional |= rotation.fract() != 0.0;
+            assert_eq!(action.angle, rotation.round() as i32);
+            pod.apply_move(action, &track);
+        }
+        assert!(fractional);
+        assert_eq!(actions.len(), commands.len());
+    }
+
+    #[test]
+    fn test_to_actions_clamps_rotation() {
+        let track = Track::new(vec![CheckPoint::from_i32(8000, 3000)], 1);
+        let start = Pod::new(0.0, 0.0, 0.0, 0.0, 0.0, 0);
+
+        // Targeting straight down from a pod facing right is only a 18 degrees turn
+        let actions = to_actions(&start, &[Command::new(0, 5000, 100)], &track);
+        assert_eq!(actions, vec![Action::new(100, 18)]);
+    }
+}
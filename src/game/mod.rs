@@ -1,9 +1,16 @@
 pub mod action;
 pub mod checkpoint;
+pub mod codec;
+pub mod command;
 pub mod pod;
 pub mod point;
+pub mod rules;
+pub mod track;
 
-use crate::game::action::Action;
-use crate::game::checkpoint::CheckPoint;
-use crate::game::pod::Pod;
-use crate::game::point::Point;
+pub use crate::game::action::{Action, parse_actions};
+pub use crate::game::checkpoint::CheckPoint;
+pub use crate::game::command::{Command, CommandMismatch, parse_log, to_actions};
+pub use crate::game::pod::Pod;
+pub use crate::game::point::Point;
+pub use crate::game::rules::GameRules;
+pub use crate::game::track::Track;
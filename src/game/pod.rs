@@ -2,7 +2,10 @@ use std::f64::consts::PI;
 
 use crate::game::action::Action;
 use crate::game::checkpoint::CheckPoint;
+use crate::game::command::{Command, CommandMismatch};
 use crate::game::point::Point;
+use crate::game::rules::GameRules;
+use crate::game::track::Track;
 
 #[derive(Debug)]
 pub struct Pod {
@@ -14,7 +17,6 @@ pub struct Pod {
     pub next_checkpoint_id: usize,
     pub done: bool,
     pub turn: usize,
-    pub max_turn: usize,
     pub last_score: f64,
 }
 
@@ -29,7 +31,6 @@ impl Pod {
             next_checkpoint_id,
             done: false,
             turn: 0,
-            max_turn: 600,
             last_score: 0.0,
         }
     }
@@ -38,6 +39,10 @@ impl Pod {
         (self.vx * self.vx + self.vy * self.vy).sqrt()
     }
 
+    pub fn is_finished(&self, track: &Track) -> bool {
+        track.is_finished(self.next_checkpoint_id)
+    }
+
     pub fn clone_pod(&self) -> Pod {
         Pod {
             x: self.x,
@@ -48,25 +53,46 @@ impl Pod {
             next_checkpoint_id: self.next_checkpoint_id,
             done: self.done,
             turn: self.turn,
-            max_turn: self.max_turn,
             last_score: self.last_score,
         }
     }
 
-    pub fn apply_moves(&mut self, actions: &[Action], checkpoints: &[CheckPoint]) {
+    pub fn apply_moves(&mut self, actions: &[Action], track: &Track) {
         for action in actions {
-            self.apply_move(action, checkpoints);
+            // Les actions restantes après la fin de la course sont ignorées
+            if self.done {
+                break;
+            }
+            self.apply_move(action, track);
         }
     }
 
-    pub fn apply_move(&mut self, action: &Action, checkpoints: &[CheckPoint]) {
-        self._rotate(action.angle as f64);
-        self._boost(action.thrust as f64);
-        self._check_cross_checkpoint(checkpoints);
+    pub fn apply_move(&mut self, action: &Action, track: &Track) {
+        self._play(action.angle as f64, action.thrust as f64, track);
+    }
+
+    pub fn apply_command(&mut self, command: &Command, track: &Track) {
+        let rotation = self.rotation_to(command, &track.rules);
+        self._play(rotation, command.thrust as f64, track);
+    }
+
+    pub fn same_state(&self, other: &Pod) -> bool {
+        self.x == other.x
+            && self.y == other.y
+            && self.vx == other.vx
+            && self.vy == other.vy
+            && self.angle == other.angle
+            && self.next_checkpoint_id == other.next_checkpoint_id
+    }
+
+    fn _play(&mut self, angle: f64, thrust: f64, track: &Track) {
+        self._rotate(angle);
+        self._boost(thrust);
+        self._check_cross_checkpoint(track);
         self._move();
-        self._end();
+        self._end(track.rules.friction);
         self.turn += 1;
-        if self.turn >= self.max_turn {
+        if self.turn >= track.rules.max_turns {
             self.done = true;
         }
     }
@@ -89,6 +115,57 @@ impl Pod {
         (px, py, action.thrust)
     }
 
+    pub fn command(&self, action: &Action) -> Command {
+        // The referee only reads integers
+        let (px, py, thrust) = self.output(action);
+        Command::new(px.round() as i32, py.round() as i32, thrust)
+    }
+
+    pub fn rotation_to(&self, command: &Command, rules: &GameRules) -> f64 {
+        // Like the referee: turn towards the target, but never more than the maximum rotation
+        let target = Point::from_i32(command.x, command.y);
+        let max_rotation = rules.max_rotation as f64;
+        self.diff_angle(&target).clamp(-max_rotation, max_rotation)
+    }
+
+    pub fn check_command(
+        &self,
+        action: &Action,
+        track: &Track,
+    ) -> Result<Command, CommandMismatch> {
+        let command = self.command(action);
+
+        let mut planned = self.clone_pod();
+        planned.apply_move(action, track);
+
+        // The rounded target is almost never exactly at the planned angle. When the small error
+        // changes the truncated speed, the neighbouring integer targets are tried as well.
+        for (dx, dy) in [
+            (0, 0),
+            (-1, 0),
+            (1, 0),
+            (0, -1),
+            (0, 1),
+            (-1, -1),
+            (-1, 1),
+            (1, -1),
+            (1, 1),
+        ] {
+            let candidate = Command::new(command.x + dx, command.y + dy, command.thrust);
+            let mut realized = self.clone_pod();
+            realized.apply_command(&candidate, track);
+            if planned.same_state(&realized) {
+                return Ok(candidate);
+            }
+        }
+
+        Err(CommandMismatch {
+            action: *action,
+            command,
+            rotation: self.rotation_to(&command, &track.rules),
+        })
+    }
+
     pub fn describe(&self) {
         eprintln!("/nPod Position       : ({}, {})", self.x, self.y);
         eprintln!("Pod Speed          : ({}, {})", self.vx, self.vy);
@@ -131,8 +208,13 @@ impl Pod {
         }
     }
 
-    pub fn fitness(&self, checkpoints: &[CheckPoint]) -> f64 {
-        let next_checkpoint = &checkpoints[self.next_checkpoint_id];
+    pub fn fitness(&self, track: &Track) -> f64 {
+        // Once finished there is no next checkpoint, an earlier crossing of the line is better
+        if self.is_finished(track) {
+            return 50_000.0 * (track.total() + 1) as f64 - self.last_score;
+        }
+
+        let next_checkpoint = track.next(self.next_checkpoint_id);
         let checkpoint_point = Point::from_f64(next_checkpoint.x, next_checkpoint.y);
         let dist_to_next = self.distance(&checkpoint_point);
 
@@ -163,12 +245,16 @@ impl Pod {
         self.vy += ra.sin() * thrust;
     }
 
-    fn _check_cross_checkpoint(&mut self, checkpoints: &[CheckPoint]) {
-        let chkpt_pos = &checkpoints[self.next_checkpoint_id];
+    fn _check_cross_checkpoint(&mut self, track: &Track) {
+        if self.is_finished(track) {
+            return;
+        }
+
+        let chkpt_pos = track.next(self.next_checkpoint_id);
         let t = self._has_collision(chkpt_pos);
         if t != -1.0 {
             self.next_checkpoint_id += 1;
-            if self.next_checkpoint_id == checkpoints.len() - 1 {
+            if self.is_finished(track) {
                 self.done = true;
             }
             self.last_score = self.turn as f64 + t;
@@ -224,11 +310,11 @@ impl Pod {
         self.y += self.vy;
     }
 
-    fn _end(&mut self) {
+    fn _end(&mut self, friction: f64) {
         self.x = self.x.trunc();
         self.y = self.y.trunc();
-        self.vx = (self.vx * 0.85).trunc();
-        self.vy = (self.vy * 0.85).trunc();
+        self.vx = (self.vx * friction).trunc();
+        self.vy = (self.vy * friction).trunc();
         self.angle = self.angle.round();
     }
 }
@@ -268,22 +354,97 @@ mod tests {
     #[test]
     fn test_apply_moves() {
         let mut pod = pod(0, 0, 0, 0, 0, 0);
-        let checkpoints = vec![checkpoint(0, 1000)];
+        let track = Track::new(vec![checkpoint(0, 1000)], 1);
         let moves: Vec<Action> = (0..10).map(|_| Action::new(200, 0)).collect();
 
         assert_eq!(pod.vx, 0.0);
         assert_eq!(pod.vy, 0.0);
 
-        pod.apply_moves(&moves, &checkpoints);
+        pod.apply_moves(&moves, &track);
 
         assert_eq!(pod.vx, 907.0);
         assert_eq!(pod.vy, 0.0);
     }
 
+    #[test]
+    fn test_apply_moves_stops_when_done() {
+        let mut pod = pod(0, 0, 500, 0, 0, 0);
+        let track = Track::new(vec![checkpoint(350, 600)], 1);
+        let moves: Vec<Action> = (0..10).map(|_| Action::new(200, 0)).collect();
+
+        pod.apply_moves(&moves, &track);
+
+        assert!(pod.done);
+        assert_eq!(pod.turn, 1);
+        assert_eq!(pod.next_checkpoint_id, 1);
+    }
+
+    #[test]
+    fn test_command_round_trip() {
+        let track = Track::new(vec![checkpoint(0, 100000)], 1);
+        let mut mismatches = 0;
+
+        for angle in [0, 45, 90, 161, 180, 270, 359] {
+            let pod = pod(10353, 1986, 0, 0, angle, 0);
+            for turn in -18..=18 {
+                let action = Action::new(200, turn);
+                match pod.check_command(&action, &track) {
+                    Ok(command) => {
+                        let mut planned = pod.clone_pod();
+                        planned.apply_move(&action, &track);
+                        let mut realized = pod.clone_pod();
+                        realized.apply_command(&command, &track);
+
+                        assert_eq!(command.thrust, 200);
+                        assert!(planned.same_state(&realized));
+                    }
+                    // e.g. 45 - 15 degrees: sin(30) * 200 lands exactly on a truncation boundary
+                    Err(mismatch) => {
+                        assert!((mismatch.rotation - turn as f64).abs() < 0.001);
+                        mismatches += 1;
+                    }
+                }
+            }
+        }
+
+        assert!(mismatches < 10, "{} mismatches", mismatches);
+    }
+
+    #[test]
+    fn test_command_is_clamped() {
+        let pod = pod(0, 0, 0, 0, 0, 0);
+        let rules = GameRules::default();
+
+        // A target right behind the pod only rotates it by 18 degrees
+        assert_eq!(
+            pod.rotation_to(&Command::new(-1000, -1, 100), &rules),
+            -18.0
+        );
+        assert_eq!(pod.rotation_to(&Command::new(0, 1000, 100), &rules), 18.0);
+
+        let rules = GameRules {
+            max_rotation: 30,
+            ..rules
+        };
+        assert_eq!(pod.rotation_to(&Command::new(0, 1000, 100), &rules), 30.0);
+    }
+
+    #[test]
+    fn test_command_mismatch() {
+        let track = Track::new(vec![checkpoint(0, 100000)], 1);
+        let pod = pod(0, 0, 0, 0, 0, 0);
+
+        // An action out of the +/-18 degrees range cannot be reproduced by the referee
+        let mismatch = pod
+            .check_command(&Action::new(100, 30), &track)
+            .unwrap_err();
+        assert_eq!(mismatch.rotation, 18.0);
+    }
+
     #[test]
     fn test_vmax_right() {
         let mut pod = pod(0, 0, 0, 0, 0, 0);
-        let checkpoints = vec![checkpoint(0, 1000)];
+        let track = Track::new(vec![checkpoint(0, 1000)], 1);
         let speeds = vec![
             170, 314, 436, 540, 629, 704, 768, 822, 868, 907, 940, 969, 993, 1014, 1031, 1046, 1059,
         ];
@@ -293,7 +454,7 @@ mod tests {
                 thrust: 200,
                 angle: 0,
             };
-            pod.apply_move(&mv, &checkpoints);
+            pod.apply_move(&mv, &track);
             assert_eq!(pod.vx, speed as f64);
             assert_eq!(pod.vy, 0.0);
         }
@@ -302,13 +463,13 @@ mod tests {
     #[test]
     fn test_vmax_left() {
         let mut pod = pod(0, 0, 0, 0, 0, 0);
-        let checkpoints = vec![checkpoint(0, 1000)];
+        let track = Track::new(vec![checkpoint(0, 1000)], 1);
         let mv = Action {
             thrust: 200,
             angle: 18,
         };
 
-        pod.apply_move(&mv, &checkpoints);
+        pod.apply_move(&mv, &track);
 
         assert_eq!(pod.angle, 18.0);
         assert_eq!(pod.vx, 161.0);
@@ -318,21 +479,38 @@ mod tests {
     #[test]
     fn test_friction() {
         let mut pod = pod(0, 0, 150, 0, 0, 0);
-        let checkpoints = vec![checkpoint(0, 1000)];
+        let track = Track::new(vec![checkpoint(0, 1000)], 1);
         let mv = Action {
             thrust: 0,
             angle: 18,
         };
 
-        pod.apply_move(&mv, &checkpoints);
+        pod.apply_move(&mv, &track);
 
         assert_eq!(pod.vx, 127.0);
     }
 
+    #[test]
+    fn test_custom_rules() {
+        let mut pod = pod(0, 0, 150, 0, 0, 0);
+        let rules = GameRules {
+            max_turns: 1,
+            friction: 0.5,
+            ..GameRules::default()
+        };
+        let track = Track::with_rules(vec![checkpoint(0, 10000)], rules);
+
+        pod.apply_move(&Action::new(100, 0), &track);
+
+        // Half of the speed is lost and the race ends with the turn limit
+        assert_eq!(pod.vx, 125.0);
+        assert!(pod.done);
+    }
+
     #[test]
     fn test_rotation() {
         let mut pod = pod(0, 0, 0, 0, 0, 0);
-        let checkpoints = vec![checkpoint(0, 10000)];
+        let track = Track::new(vec![checkpoint(0, 10000)], 1);
 
         assert_eq!(pod.angle, 0.0);
 
@@ -341,7 +519,7 @@ mod tests {
                 thrust: 200,
                 angle: 18,
             },
-            &checkpoints,
+            &track,
         );
         assert_eq!(pod.angle, 18.0);
 
@@ -350,7 +528,7 @@ mod tests {
                 thrust: 200,
                 angle: 18,
             },
-            &checkpoints,
+            &track,
         );
         assert_eq!(pod.angle, 36.0);
 
@@ -359,7 +537,7 @@ mod tests {
                 thrust: 200,
                 angle: -18,
             },
-            &checkpoints,
+            &track,
         );
         assert_eq!(pod.angle, 18.0);
 
@@ -368,7 +546,7 @@ mod tests {
                 thrust: 200,
                 angle: -18,
             },
-            &checkpoints,
+            &track,
         );
         assert_eq!(pod.angle, 0.0);
 
@@ -377,7 +555,7 @@ mod tests {
                 thrust: 200,
                 angle: 15,
             },
-            &checkpoints,
+            &track,
         );
         assert_eq!(pod.angle, 15.0);
     }
@@ -385,14 +563,14 @@ mod tests {
     #[test]
     fn test_rotation_2() {
         let mut pod = pod(0, 0, 5, 5, 45, 0);
-        let checkpoints = vec![checkpoint(0, 10000)];
+        let track = Track::new(vec![checkpoint(0, 10000)], 1);
 
         pod.apply_move(
             &Action {
                 thrust: 200,
                 angle: -18,
             },
-            &checkpoints,
+            &track,
         );
         assert_eq!(pod.angle, 27.0);
 
@@ -401,7 +579,7 @@ mod tests {
                 thrust: 200,
                 angle: -18,
             },
-            &checkpoints,
+            &track,
         );
         assert_eq!(pod.angle, 9.0);
 
@@ -410,7 +588,7 @@ mod tests {
                 thrust: 200,
                 angle: -18,
             },
-            &checkpoints,
+            &track,
         );
         assert_eq!(pod.angle, 351.0);
 
@@ -419,7 +597,7 @@ mod tests {
                 thrust: 200,
                 angle: 10,
             },
-            &checkpoints,
+            &track,
         );
         assert_eq!(pod.angle, 1.0);
 
@@ -428,7 +606,7 @@ mod tests {
                 thrust: 200,
                 angle: -1,
             },
-            &checkpoints,
+            &track,
         );
         assert_eq!(pod.angle, 0.0);
     }
@@ -436,14 +614,14 @@ mod tests {
     #[test]
     fn test_cross_checkpoint_1() {
         let mut pod = pod(0, 0, 500, 0, 0, 0);
-        let checkpoints = vec![checkpoint(350, 600), checkpoint(0, 100000)];
+        let track = Track::new(vec![checkpoint(350, 600)], 1);
 
         pod.apply_move(
             &Action {
                 thrust: 200,
                 angle: 0,
             },
-            &checkpoints,
+            &track,
         );
         assert_eq!(pod.x, 700.0);
         assert_eq!(pod.next_checkpoint_id, 1);
@@ -452,14 +630,14 @@ mod tests {
     #[test]
     fn test_cross_checkpoint_2() {
         let mut pod = pod(0, 0, 500, 0, 0, 0);
-        let checkpoints = vec![checkpoint(350, 599), checkpoint(0, 100000)];
+        let track = Track::new(vec![checkpoint(350, 599)], 1);
 
         pod.apply_move(
             &Action {
                 thrust: 200,
                 angle: 0,
             },
-            &checkpoints,
+            &track,
         );
         assert_eq!(pod.x, 700.0);
         assert_eq!(pod.next_checkpoint_id, 1);
@@ -467,31 +645,35 @@ mod tests {
 
     #[test]
     fn test_cross_checkpoint_3() {
+        // Only the next checkpoint counts: the pod aims at (0, 10000) and passes by (0, 599)
         let mut pod = pod(0, 0, 500, 0, 0, 1);
-        let checkpoints = vec![checkpoint(0, 599), checkpoint(0, 10000)];
+        let track = Track::new(vec![checkpoint(0, 599), checkpoint(0, 10000)], 1);
+        assert_eq!(pod.next_checkpoint_id, 1);
+        assert!(!pod.done);
 
         pod.apply_move(
             &Action {
                 thrust: 200,
                 angle: 0,
             },
-            &checkpoints,
+            &track,
         );
         assert_eq!(pod.x, 700.0);
         assert_eq!(pod.next_checkpoint_id, 1);
+        assert!(!pod.done);
     }
 
     #[test]
     fn test_cross_checkpoint_4() {
         let mut pod = pod(0, 0, 500, 0, 0, 0);
-        let checkpoints = vec![checkpoint(700, 599), checkpoint(0, 10000)];
+        let track = Track::new(vec![checkpoint(700, 599)], 1);
 
         pod.apply_move(
             &Action {
                 thrust: 200,
                 angle: 0,
             },
-            &checkpoints,
+            &track,
         );
         assert_eq!(pod.x, 700.0);
         assert_eq!(pod.next_checkpoint_id, 1);
@@ -500,14 +682,14 @@ mod tests {
     #[test]
     fn test_cross_checkpoint_5() {
         let mut pod = pod(150, 0, 127, 0, 0, 0);
-        let checkpoints = vec![checkpoint(800, 0), checkpoint(0, 10000)];
+        let track = Track::new(vec![checkpoint(800, 0)], 1);
 
         pod.apply_move(
             &Action {
                 thrust: 200,
                 angle: 0,
             },
-            &checkpoints,
+            &track,
         );
         assert_eq!(pod.x, 477.0);
         assert_eq!(pod.next_checkpoint_id, 1);
@@ -516,14 +698,14 @@ mod tests {
     #[test]
     fn test_cross_checkpoint_6() {
         let mut pod = pod(150, 0, 1100, 0, 0, 0);
-        let checkpoints = vec![checkpoint(800, 50), checkpoint(0, 10000)];
+        let track = Track::new(vec![checkpoint(800, 50)], 1);
 
         pod.apply_move(
             &Action {
                 thrust: 200,
                 angle: 0,
             },
-            &checkpoints,
+            &track,
         );
         assert_eq!(pod.x, 1450.0);
         assert_eq!(pod.next_checkpoint_id, 1);
@@ -532,14 +714,14 @@ mod tests {
     #[test]
     fn test_cross_checkpoint_7() {
         let mut pod = pod(1450, 0, -1100, 0, 180, 0);
-        let checkpoints = vec![checkpoint(800, 50), checkpoint(0, 10000)];
+        let track = Track::new(vec![checkpoint(800, 50)], 1);
 
         pod.apply_move(
             &Action {
                 thrust: 200,
                 angle: 0,
             },
-            &checkpoints,
+            &track,
         );
         assert_eq!(pod.x, 150.0);
         assert_eq!(pod.next_checkpoint_id, 1);
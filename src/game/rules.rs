@@ -0,0 +1,142 @@
+use crate::game::action::Action;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GameRules {
+    pub laps: usize,
+    pub max_turns: usize,
+    pub checkpoint_radius: f64,
+    pub friction: f64,
+    pub max_thrust: i32,
+    // Degrees the pod can turn in one turn, in each direction
+    pub max_rotation: i32,
+}
+
+pub const RULES: [&str; 3] = ["search-race", "coders-strike-back", "one-lap"];
+
+impl GameRules {
+    pub fn search_race() -> Self {
+        GameRules {
+            laps: 3,
+            max_turns: 600,
+            checkpoint_radius: 600.0,
+            friction: 0.85,
+            max_thrust: 200,
+            max_rotation: 18,
+        }
+    }
+
+    pub fn coders_strike_back() -> Self {
+        // Without the boost, the shield and the 100 turns timeout between two checkpoints
+        GameRules {
+            max_thrust: 100,
+            ..GameRules::search_race()
+        }
+    }
+
+    pub fn one_lap() -> Self {
+        // A third of a race, for quicker tuning runs
+        GameRules {
+            laps: 1,
+            max_turns: 200,
+            ..GameRules::search_race()
+        }
+    }
+
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name {
+            "search-race" => Some(GameRules::search_race()),
+            "coders-strike-back" => Some(GameRules::coders_strike_back()),
+            "one-lap" => Some(GameRules::one_lap()),
+            _ => None,
+        }
+    }
+
+    pub fn allows(&self, action: &Action) -> bool {
+        (0..=self.max_thrust).contains(&action.thrust)
+            && (-self.max_rotation..=self.max_rotation).contains(&action.angle)
+    }
+
+    pub fn clamp(&self, action: &Action) -> Action {
+        Action::new(
+            action.thrust.clamp(0, self.max_thrust),
+            action.angle.clamp(-self.max_rotation, self.max_rotation),
+        )
+    }
+
+    pub fn all_actions(&self) -> Vec<Action> {
+        (0..=self.max_thrust)
+            .flat_map(|thrust| {
+                (-self.max_rotation..=self.max_rotation)
+                    .map(move |angle| Action::new(thrust, angle))
+            })
+            .collect()
+    }
+
+    // Evenly spaced values, both bounds included
+    pub fn action_grid(&self, thrusts: usize, angles: usize) -> Vec<Action> {
+        let steps = |min: i32, max: i32, n: usize| -> Vec<i32> {
+            match n {
+                0 => Vec::new(),
+                1 => vec![(min + max) / 2],
+                _ => (0..n)
+                    .map(|i| min + (max - min) * i as i32 / (n - 1) as i32)
+                    .collect(),
+            }
+        };
+
+        let thrusts = steps(0, self.max_thrust, thrusts);
+        let angles = steps(-self.max_rotation, self.max_rotation, angles);
+        thrusts
+            .iter()
+            .flat_map(|&thrust| angles.iter().map(move |&angle| Action::new(thrust, angle)))
+            .collect()
+    }
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        GameRules::search_race()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presets() {
+        for name in RULES {
+            assert!(GameRules::by_name(name).is_some(), "{}", name);
+        }
+        assert_eq!(GameRules::default(), GameRules::search_race());
+        assert!(GameRules::by_name("unknown").is_none());
+    }
+
+    #[test]
+    fn test_action_bounds() {
+        let rules = GameRules::coders_strike_back();
+
+        assert!(rules.allows(&Action::new(100, -18)));
+        assert!(!rules.allows(&Action::new(200, 0)));
+        assert_eq!(rules.clamp(&Action::new(200, 30)), Action::new(100, 18));
+        assert_eq!(rules.all_actions().len(), 101 * 37);
+        assert!(
+            rules
+                .all_actions()
+                .iter()
+                .all(|action| rules.allows(action))
+        );
+    }
+
+    #[test]
+    fn test_action_grid() {
+        let rules = GameRules::default();
+        let grid = rules.action_grid(5, 5);
+
+        assert_eq!(grid.len(), 25);
+        assert_eq!(grid[0], Action::new(0, -18));
+        assert_eq!(grid[6], Action::new(50, -9));
+        assert_eq!(grid[24], Action::new(200, 18));
+        assert_eq!(rules.action_grid(1, 1), vec![Action::new(100, 0)]);
+    }
+}
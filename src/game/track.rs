@@ -0,0 +1,121 @@
+use crate::game::checkpoint::CheckPoint;
+use crate::game::rules::GameRules;
+
+// The checkpoints of one lap in race order. The start line is the last checkpoint of a lap,
+// the race is finished once it has been crossed at the end of the last lap.
+#[derive(Debug, PartialEq)]
+pub struct Track {
+    pub checkpoints: Vec<CheckPoint>,
+    pub rules: GameRules,
+}
+
+impl Track {
+    pub fn new(checkpoints: Vec<CheckPoint>, laps: usize) -> Self {
+        Track::with_rules(
+            checkpoints,
+            GameRules {
+                laps,
+                ..GameRules::default()
+            },
+        )
+    }
+
+    pub fn with_rules(checkpoints: Vec<CheckPoint>, rules: GameRules) -> Self {
+        // next and start index into the lap, a track without checkpoints has no race
+        assert!(
+            !checkpoints.is_empty(),
+            "a track needs at least one checkpoint"
+        );
+        let checkpoints = checkpoints
+            .into_iter()
+            .map(|checkpoint| checkpoint.with_radius(rules.checkpoint_radius))
+            .collect();
+        Track { checkpoints, rules }
+    }
+
+    pub fn from_map(map: &[CheckPoint], rules: GameRules) -> Self {
+        // The map lists the start line first, it is crossed after every other checkpoint
+        let checkpoints = map
+            .iter()
+            .skip(1)
+            .chain(map.first())
+            .map(CheckPoint::clone_checkpoint)
+            .collect();
+        Track::with_rules(checkpoints, rules)
+    }
+
+    pub fn len(&self) -> usize {
+        self.checkpoints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.checkpoints.is_empty()
+    }
+
+    pub fn laps(&self) -> usize {
+        self.rules.laps
+    }
+
+    // Checkpoints to cross over the whole race
+    pub fn total(&self) -> usize {
+        self.checkpoints.len() * self.rules.laps
+    }
+
+    // The checkpoint to reach once `id` checkpoints have been crossed
+    pub fn next(&self, id: usize) -> &CheckPoint {
+        &self.checkpoints[id % self.checkpoints.len()]
+    }
+
+    pub fn start(&self) -> &CheckPoint {
+        &self.checkpoints[self.checkpoints.len() - 1]
+    }
+
+    pub fn is_finished(&self, id: usize) -> bool {
+        id >= self.total()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_map() {
+        let map = vec![
+            CheckPoint::from_i32(10353, 1986),
+            CheckPoint::from_i32(2757, 4659),
+            CheckPoint::from_i32(3358, 2838),
+        ];
+        let track = Track::from_map(&map, GameRules::default());
+
+        assert_eq!(track.len(), 3);
+        assert_eq!(track.laps(), 3);
+        assert_eq!(track.total(), 9);
+        assert_eq!(track.start(), &map[0]);
+        assert_eq!(track.next(0), &map[1]);
+        assert_eq!(track.next(2), &map[0]);
+        assert_eq!(track.next(4), &map[2]);
+        assert!(!track.is_finished(8));
+        assert!(track.is_finished(9));
+    }
+
+    #[test]
+    fn test_rules() {
+        let map = vec![CheckPoint::from_i32(0, 0), CheckPoint::from_i32(5000, 0)];
+        let track = Track::from_map(&map, GameRules::one_lap());
+
+        assert_eq!(track.total(), 2);
+        let wide = GameRules {
+            checkpoint_radius: 1000.0,
+            ..GameRules::default()
+        };
+        let track = Track::from_map(&map, wide);
+        assert_eq!(track.next(0).r2, 1_000_000.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one checkpoint")]
+    fn test_empty_track() {
+        Track::from_map(&[], GameRules::default());
+    }
+}
@@ -0,0 +1,12 @@
+pub mod batch;
+pub mod bot;
+pub mod error;
+pub mod game;
+pub mod race;
+pub mod render;
+pub mod search;
+pub mod store;
+pub mod submission;
+pub mod verify;
+
+pub use race::{Race, get_initial_pod, load_testcase};
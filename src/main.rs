@@ -1,246 +1,267 @@
-mod game;
+mod cli;
 
-use std::fs::File;
-use std::io::Read;
+use std::fs;
+use std::io;
 use std::path::Path;
 use std::time::Instant;
 
-use serde::{Deserialize, Serialize};
+use serde_json::json;
 
-use game::action::Action;
-use game::checkpoint::CheckPoint;
-use game::pod::Pod;
-use game::point::Point;
+use cg_search_race_rust::error::Error;
+use cg_search_race_rust::game::{Action, parse_actions};
+use cg_search_race_rust::search::{self, Budget};
+use cg_search_race_rust::store::{Store, Submission};
+use cg_search_race_rust::{Race, batch, bot, render, submission, verify};
 
-#[derive(Debug, Serialize, Deserialize)]
-struct TestData {
-    #[serde(rename = "testIn")]
-    test_in: String,
-    // Ajoutez d'autres champs si nécessaires
-}
-
-fn load_testcase<P: AsRef<Path>>(testcase: P) -> Vec<CheckPoint> {
-    let mut file = File::open(testcase).expect("Failed to open testcase file");
-    let mut content = String::new();
-    file.read_to_string(&mut content)
-        .expect("Failed to read testcase file");
-
-    let mut checkpoints: Vec<CheckPoint> = Vec::new();
+use cli::{Format, Options, Subcommand};
 
-    let data: Option<TestData> = serde_json::from_str(&content).expect("Failed to parse JSON");
-    if let Some(data) = &data {
-        let mut all_pts = Vec::new();
+// Library errors, parse errors and plain messages all end up printed the same way
+type CliResult<T> = Result<T, Box<dyn std::error::Error>>;
 
-        for s in data.test_in.split(';') {
-            let coords: Vec<i32> = s.split(' ').filter_map(|x| x.parse::<i32>().ok()).collect();
-            if coords.len() >= 2 {
-                all_pts.push(CheckPoint::from_i32(coords[0], coords[1]));
-            }
-        }
+fn read_actions(actions: &str) -> CliResult<Vec<Action>> {
+    // Either the action string itself or a file containing it
+    let path = Path::new(actions);
+    if !path.is_file() {
+        return Ok(parse_actions(actions)?);
+    }
 
-        for _ in 0..3 {
-            for checkpoint in all_pts[1..].iter() {
-                checkpoints.push(checkpoint.clone_checkpoint());
-            }
-            checkpoints.push(all_pts[0].clone_checkpoint());
+    let content = fs::read_to_string(path).map_err(|source| Error::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    parse_actions(&content).map_err(|source| {
+        Error::Parse {
+            path: path.to_path_buf(),
+            source,
         }
+        .into()
+    })
+}
 
-        let n_minus2 = &checkpoints[checkpoints.len() - 2];
-        let n_minus1 = &checkpoints[checkpoints.len() - 1];
-
-        // Conversion en Point pour utiliser les opérations
-        let n_minus2_point = Point::from_f64(n_minus2.x, n_minus2.y);
-        let n_minus1_point = Point::from_f64(n_minus1.x, n_minus1.y);
-
-        let dist = n_minus2.distance(&n_minus1_point);
-        let factor = 50_000.0 / dist;
-
-        // Calcul du dernier point
-        // last_pt = n_minus1 * (factor+1) - n_minus2 * factor
-        let n_minus1_scaled = &n_minus1_point * (factor + 1.0);
-        let n_minus2_scaled = &n_minus2_point * factor;
-        let last_pt = Point::from_f64(
-            (n_minus1_scaled.x - n_minus2_scaled.x).trunc(),
-            (n_minus1_scaled.y - n_minus2_scaled.y).trunc(),
-        );
+fn budget(options: &Options) -> Budget {
+    match options.time {
+        Some(limit) => Budget::time(limit),
+        None => Budget::unlimited(),
+    }
+}
 
-        checkpoints.push(CheckPoint::from_f64(last_pt.x, last_pt.y));
+fn emit(options: &Options, content: &str) -> CliResult<()> {
+    match &options.output {
+        Some(path) => fs::write(path, content).map_err(|source| Error::Io {
+            path: path.clone(),
+            source,
+        })?,
+        None => println!("{}", content),
     }
+    Ok(())
+}
 
-    checkpoints
+fn save(
+    store: &Path,
+    map: &str,
+    race: &Race,
+    actions: &[Action],
+    options: &Options,
+) -> CliResult<()> {
+    let store = Store::open(store)?;
+    let submission = store.submit(map, race, actions, &options.algorithm, options.seed)?;
+
+    match submission {
+        Submission::Accepted {
+            previous: Some(previous),
+        } => eprintln!("{}: new best, was {:.2}", map, previous),
+        Submission::Accepted { previous: None } => eprintln!("{}: first solution", map),
+        Submission::Rejected { best } => eprintln!("{}: kept best {:.2}", map, best),
+        Submission::Invalid(reason) => eprintln!("{}: not stored, {}", map, reason),
+    }
+    Ok(())
 }
 
-pub fn get_initial_pod(checkpoints: &[CheckPoint]) -> Pod {
-    let starting_position = &checkpoints[checkpoints.len() - 2];
-    let mut pod = Pod::new(starting_position.x, starting_position.y, 0.0, 0.0, 0.0, 0);
+fn solve(map: &Path, options: &Options) -> CliResult<String> {
+    let race = Race::load_with_rules(map, options.rules)?;
+    let pod = race.initial_pod();
+    let name = map.file_stem().unwrap_or_default().to_string_lossy();
+    let initial = match &options.store {
+        Some(store) => Store::open(store)?.actions(&name)?,
+        None => Vec::new(),
+    };
+    let mut search = search::by_name_from(&options.algorithm, options.seed, initial)
+        .ok_or_else(|| Error::UnknownSolver(options.algorithm.clone()))?;
 
-    let checkpoint_point = Point::from_f64(checkpoints[0].x, checkpoints[0].y);
-    let angle = pod.get_angle(&checkpoint_point);
-    pod.angle = angle.round();
+    let start = Instant::now();
+    let result = search.search(&race.track, &pod, &mut budget(options));
+    let elapsed = start.elapsed();
 
-    pod.done = false;
-    pod.turn = 0;
+    if let Some(store) = &options.store {
+        save(store, &name, &race, &result.actions, options)?;
+    }
 
-    pod
+    Ok(match options.format {
+        Format::Text => format!(
+            "Final Score: {}\nFinished: {}\nTime elapsed: {:?}\n{}",
+            result.score,
+            result.finished,
+            elapsed,
+            result.action_string()
+        ),
+        Format::Json => json!({
+            "map": map.to_string_lossy(),
+            "algorithm": options.algorithm,
+            "seed": options.seed,
+            "score": result.score,
+            "finished": result.finished,
+            "turns": result.actions.len(),
+            "time_ms": elapsed.as_millis() as u64,
+            "actions": result.action_string(),
+        })
+        .to_string(),
+    })
 }
 
-fn main() {
-    let checkpoints = load_testcase("testcases/test1.json");
-    let mut base_pod = get_initial_pod(&checkpoints);
-
-    let all_possible_actions: Vec<Action> = (0..=200)
-        .flat_map(|thrust| (-18..=18).map(move |angle| Action::new(thrust, angle)))
-        .collect();
-
-    let start = Instant::now();
-    let mut all_actions: Vec<&Action> = Vec::new();
-    while !base_pod.done {
-        let mut best_score = 0.0;
-        let mut best_action = &all_possible_actions[0];
-        for action in all_possible_actions.iter() {
-            let mut pod = base_pod.clone_pod();
-            pod.apply_move(action, &checkpoints);
-            let score = pod.fitness(&checkpoints);
-
-            if score > best_score {
-                best_score = score;
-                best_action = action;
-            }
+fn run_batch(dir: &Path, options: &Options) -> CliResult<String> {
+    let store = options.store.as_deref().map(Store::open).transpose()?;
+    let entries = batch::run_batch(
+        dir,
+        &options.algorithm,
+        options.seed,
+        options.time,
+        options.rules,
+        store.as_ref(),
+    )?;
+
+    if let Some(store) = &options.store {
+        for (map, entry) in batch::list_maps(dir)?.iter().zip(&entries) {
+            save(
+                store,
+                &entry.map,
+                &Race::load_with_rules(map, options.rules)?,
+                &entry.actions,
+                options,
+            )?;
         }
-        base_pod.apply_move(best_action, &checkpoints);
-        all_actions.push(best_action);
     }
 
-    println!("Final Score: {}", base_pod.last_score);
-    println!("Time elapsed using Instant: {:?}", start.elapsed());
-
-    let end_string = all_actions
-        .iter()
-        .map(|action| action.to_string())
-        .collect::<Vec<String>>()
-        .join(";");
-    println!("{}", end_string);
+    Ok(match options.format {
+        Format::Text => batch::scoreboard(&entries),
+        Format::Json => json!(
+            entries
+                .iter()
+                .map(|entry| json!({
+                    "map": entry.map,
+                    "score": entry.score,
+                    "finished": entry.finished,
+                    "turns": entry.turns,
+                    "checkpoints": entry.checkpoints,
+                    "total_checkpoints": entry.total_checkpoints,
+                    "time_ms": entry.elapsed.as_millis() as u64,
+                }))
+                .collect::<Vec<_>>()
+        )
+        .to_string(),
+    })
+}
 
-    // println!("Pod: {:?}", pod);
-    // println!("Checkpoints: {:?}", checkpoints);
+fn verify(map: &Path, actions: &str, options: &Options) -> CliResult<String> {
+    let race = Race::load_with_rules(map, options.rules)?;
+    let report = verify::verify(&race, &read_actions(actions)?);
+
+    Ok(match options.format {
+        Format::Text => report.to_string().trim_end().to_string(),
+        Format::Json => json!({
+            "map": map.to_string_lossy(),
+            "score": report.score,
+            "finished": report.finished,
+            "turns": report.turns,
+            "checkpoints": report
+                .crossings
+                .iter()
+                .map(|crossing| json!({
+                    "checkpoint": crossing.checkpoint,
+                    "turn": crossing.turn,
+                    "time": crossing.time,
+                }))
+                .collect::<Vec<_>>(),
+            "invalid": report
+                .invalid
+                .iter()
+                .map(|(turn, action)| json!({ "turn": turn, "action": action.to_string() }))
+                .collect::<Vec<_>>(),
+            "ignored": report.ignored,
+        })
+        .to_string(),
+    })
+}
 
-    // Vous pouvez ajouter d'autres logiques de jeu ici
+fn submit(dir: &Path, options: &Options) -> CliResult<String> {
+    let store = options
+        .store
+        .as_ref()
+        .ok_or("submit needs the --store of solutions to embed")?;
+    let store = Store::open(store)?;
+    let solutions = submission::solutions_from_store(&store, dir)?;
+
+    let submission = submission::generate(&solutions)?;
+    eprintln!(
+        "{} solutions embedded, {} skipped, {} characters",
+        submission.embedded.len(),
+        submission.skipped.len(),
+        submission.source.chars().count()
+    );
+    Ok(submission.source)
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::{
-        game::{action::Action, checkpoint::CheckPoint},
-        get_initial_pod, load_testcase,
+fn render(map: &Path, actions: Option<&str>, options: &Options) -> CliResult<String> {
+    let actions = match actions {
+        Some(actions) => read_actions(actions)?,
+        None => Vec::new(),
     };
+    Ok(render::svg(
+        &Race::load_with_rules(map, options.rules)?,
+        &actions,
+    ))
+}
 
-    #[test]
-    fn test_start_position() {
-        let checkpoints = load_testcase("testcases/test1.json");
-        let pod = get_initial_pod(&checkpoints);
-
-        assert_eq!(pod.x, 10353.0);
-        assert_eq!(pod.y, 1986.0);
-        assert_eq!(pod.angle.round(), 161.0);
-        assert_eq!(pod.vx, 0.0);
-        assert_eq!(pod.vy, 0.0);
-        assert_eq!(pod.next_checkpoint_id, 0);
-    }
-
-    #[test]
-    fn test_checkpoint_list() {
-        let checkpoints = load_testcase("testcases/test1.json");
-
-        let expected = vec![
-            CheckPoint::from_i32(2757, 4659),
-            CheckPoint::from_i32(3358, 2838),
-            CheckPoint::from_i32(10353, 1986),
-            CheckPoint::from_i32(2757, 4659),
-            CheckPoint::from_i32(3358, 2838),
-            CheckPoint::from_i32(10353, 1986),
-            CheckPoint::from_i32(2757, 4659),
-            CheckPoint::from_i32(3358, 2838),
-            CheckPoint::from_i32(10353, 1986),
-            CheckPoint::from_i32(59986, -4059),
-        ];
-
-        assert_eq!(checkpoints, expected);
-    }
-
-    #[test]
-    fn test_apply_move() {
-        let checkpoints = vec![
-            CheckPoint::from_i32(800, 0),
-            CheckPoint::from_i32(2200, 0),
-            CheckPoint::from_i32(3600, 0),
-        ];
-        let mut pod = get_initial_pod(&checkpoints);
-
-        assert_eq!(pod.turn, 0);
-
-        pod.apply_move(&Action::new(1, 0), &checkpoints);
-        assert_eq!(pod.turn, 1);
-        assert!(!pod.done);
+fn run_bot(options: &Options) -> CliResult<()> {
+    let planner = search::planner_by_name(&options.algorithm, options.seed)
+        .ok_or(format!("'{}' cannot play turn by turn", options.algorithm))?;
+    let stdin = io::stdin();
+    Ok(bot::run(
+        &mut stdin.lock(),
+        &mut io::stdout(),
+        planner,
+        options.rules,
+    )?)
+}
 
-        for _ in 0..598 {
-            pod.apply_move(&Action::new(1, 0), &checkpoints);
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (subcommand, options) = match cli::parse(&args) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
         }
-        assert_eq!(pod.turn, 599);
-        assert!(!pod.done);
-
-        pod.apply_move(&Action::new(1, 0), &checkpoints);
-        assert_eq!(pod.turn, 600);
-        assert!(pod.done);
-    }
-
-    #[test]
-    fn test_apply_moves() {
-        let checkpoints = vec![
-            CheckPoint::from_i32(800, 0),
-            CheckPoint::from_i32(2200, 0),
-            CheckPoint::from_i32(3600, 0),
-        ];
-        let mut pod = get_initial_pod(&checkpoints);
-
-        assert_eq!(pod.turn, 0);
-
-        pod.apply_move(&Action::new(1, 0), &checkpoints);
-        assert_eq!(pod.turn, 1);
-        assert!(!pod.done);
-
-        let actions: Vec<Action> = (0..598).map(|_| Action::new(1, 0)).collect();
-        pod.apply_moves(&actions, &checkpoints);
-        assert_eq!(pod.turn, 599);
-        assert!(!pod.done);
-
-        pod.apply_move(&Action::new(1, 0), &checkpoints);
-        assert_eq!(pod.turn, 600);
-        assert!(pod.done);
-    }
-
-    #[test]
-    fn test_simulation_1() {
-        let checkpoints = load_testcase("testcases/test13.json");
-        let mut pod = get_initial_pod(&checkpoints);
-
-        assert_eq!(pod.x, 13332.0);
-        assert_eq!(pod.y, 4114.0);
-        assert_eq!(pod.angle.round(), 154.0);
-        assert_eq!(pod.vx, 0.0);
-        assert_eq!(pod.vy, 0.0);
-        assert_eq!(pod.next_checkpoint_id, 0);
-
-        let command = "200,-14;200,-1;200,14;200,7;200,-12;176,0;200,18;116,18;100,18;0,18;0,18;15,18;146,18;200,18;200,18;195,18;200,9;200,6;200,15;200,15;200,11;200,11;161,-3;191,18;98,18;18,18;0,18;20,18;87,11;200,18;197,18;200,18;200,18;200,14;151,-7;166,9;200,-8;200,10;197,-1;200,7;200,-7;200,-18;200,-18;123,-18;7,-18;0,-18;0,-18;44,-18;58,-18;158,-18;176,-18;200,-18;200,-18;200,-17;200,-15;200,-2;200,0;200,9;198,-9;184,17;184,8;173,18;157,18;120,12;0,18;178,18;131,16;200,15;200,18;200,15;200,11;200,-9;200,9;200,4;194,10;200,14;171,16;200,15;16,18;53,18;0,18;200,18;200,15;200,18;194,18;200,12;200,14;176,8;143,15;188,18;192,-18;200,1;157,-18;11,-18;13,-18;0,-18;0,-18;3,-18;200,-18;200,-18;200,-16;200,-18;200,-3;200,-11;200,-18;200,6;200,10;200,2;200,-4;191,0;200,-18;200,-18;195,-18;37,-16;27,-18;0,-18;22,-18;5,-18;103,-18;200,-18;197,-9;200,-8;200,-6;200,-18;200,2;200,-5;200,6;194,11;200,18;200,8;200,18;149,9;91,18;64,14;114,13;160,18;200,18;200,18;200,11;190,-13;200,18;200,11;200,3;187,-4;200,18;198,18;92,18;90,18;12,18;174,18;199,18;200,18;200,14;200,18;200,14;200,3;200,18;199,1;200,15;200,5;193,15;92,18;0,18;10,18;0,18;43,18;200,18;200,18;200,18;200,11;200,0;198,0;200,18;200,-6;200,11;200,-11;200,-9;185,-10;198,-8;176,-12;37,-18;9,-18;0,-18;0,-18;48,-18;145,-18;200,-18;200,-18;200,-18;200,-18;200,-18;200,-18;200,18;149,-17;199,7;71,-10;179,5;169,18;157,18;139,10;185,16;187,16;176,15;0,18;200,18;200,18;200,0;200,-8";
+    };
 
-        let actions: Vec<Action> = command.split(';').map(Action::from).collect();
+    let result = match &subcommand {
+        Subcommand::Solve { map } => solve(map, &options),
+        Subcommand::Batch { dir } => run_batch(dir, &options),
+        Subcommand::Verify { map, actions } => verify(map, actions, &options),
+        Subcommand::Render { map, actions } => render(map, actions.as_deref(), &options),
+        Subcommand::Submit { dir } => submit(dir, &options),
+        Subcommand::Bot => run_bot(&options).map(|_| String::new()),
+    };
 
-        pod.apply_moves(&actions, &checkpoints);
-        if pod.done {
-            let error_score = pod.last_score - 207.57;
-            assert!(error_score.abs() < 0.1, "Error score: {}", error_score);
-            return;
+    let result = result.and_then(|content| {
+        if content.is_empty() {
+            Ok(())
+        } else {
+            emit(&options, &content)
         }
+    });
 
-        panic!("Game should have ended before the last action");
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(1);
     }
 }
@@ -0,0 +1,328 @@
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, ParseError};
+use crate::game::action::Action;
+use crate::game::checkpoint::CheckPoint;
+use crate::game::command;
+use crate::game::pod::Pod;
+use crate::game::point::Point;
+use crate::game::rules::GameRules;
+use crate::game::track::Track;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TestData {
+    #[serde(rename = "testIn")]
+    test_in: String,
+    // Ajoutez d'autres champs si nécessaires
+}
+
+pub fn parse_points(test_in: &str) -> Result<Vec<CheckPoint>, ParseError> {
+    // "x y;x y;...", the first point is the start
+    let mut all_pts = Vec::new();
+    for (i, point) in test_in.split(';').map(str::trim).enumerate() {
+        if point.is_empty() {
+            continue;
+        }
+
+        let mut coords = point.split_whitespace();
+        let mut coord = |field: &'static str| {
+            let token = coords.next().unwrap_or("");
+            token
+                .parse::<i32>()
+                .map_err(|_| ParseError::new(field, token).at(i))
+        };
+        let x = coord("x")?;
+        let y = coord("y")?;
+        all_pts.push(CheckPoint::from_i32(x, y));
+    }
+
+    if all_pts.len() < 2 {
+        return Err(ParseError::new("map", test_in.trim()));
+    }
+    Ok(all_pts)
+}
+
+pub fn load_points<P: AsRef<Path>>(testcase: P) -> Result<Vec<CheckPoint>, Error> {
+    let path = testcase.as_ref().to_path_buf();
+    let content = fs::read_to_string(&path).map_err(|source| Error::Io {
+        path: path.clone(),
+        source,
+    })?;
+
+    let data: TestData = serde_json::from_str(&content).map_err(|e| Error::Json {
+        path: path.clone(),
+        message: e.to_string(),
+    })?;
+    parse_points(&data.test_in).map_err(|source| Error::Parse { path, source })
+}
+
+pub fn load_testcase<P: AsRef<Path>>(testcase: P) -> Result<Track, Error> {
+    Ok(Track::from_map(
+        &load_points(testcase)?,
+        GameRules::default(),
+    ))
+}
+
+pub fn get_initial_pod(track: &Track) -> Pod {
+    // The pod waits on the start line, facing the first checkpoint
+    let starting_position = track.start();
+    let mut pod = Pod::new(starting_position.x, starting_position.y, 0.0, 0.0, 0.0, 0);
+
+    let first = track.next(0);
+    let checkpoint_point = Point::from_f64(first.x, first.y);
+    let angle = pod.get_angle(&checkpoint_point);
+    pod.angle = angle.round();
+
+    pod.done = false;
+    pod.turn = 0;
+
+    pod
+}
+
+#[derive(Debug)]
+pub struct Race {
+    pub track: Track,
+}
+
+impl Race {
+    pub fn new(track: Track) -> Self {
+        Race { track }
+    }
+
+    pub fn from_points(all_pts: &[CheckPoint]) -> Self {
+        Race::new(Track::from_map(all_pts, GameRules::default()))
+    }
+
+    pub fn load<P: AsRef<Path>>(testcase: P) -> Result<Self, Error> {
+        Race::load_with_rules(testcase, GameRules::default())
+    }
+
+    pub fn load_with_rules<P: AsRef<Path>>(testcase: P, rules: GameRules) -> Result<Self, Error> {
+        Ok(Race::new(Track::from_map(&load_points(testcase)?, rules)))
+    }
+
+    pub fn initial_pod(&self) -> Pod {
+        get_initial_pod(&self.track)
+    }
+
+    pub fn step(&self, pod: &mut Pod, action: &Action) {
+        pod.apply_move(action, &self.track);
+    }
+
+    pub fn simulate(&self, actions: &[Action]) -> Pod {
+        let mut pod = self.initial_pod();
+        pod.apply_moves(actions, &self.track);
+        pod
+    }
+
+    pub fn import_commands(&self, log: &str) -> Result<Vec<Action>, String> {
+        let commands = command::parse_log(log)?;
+        Ok(command::to_actions(
+            &self.initial_pod(),
+            &commands,
+            &self.track,
+        ))
+    }
+
+    pub fn is_finished(&self, pod: &Pod) -> bool {
+        pod.is_finished(&self.track)
+    }
+
+    pub fn score(&self, actions: &[Action]) -> Option<f64> {
+        // Only a race that crossed the last checkpoint has a meaningful score
+        let pod = self.simulate(actions);
+        if self.is_finished(&pod) {
+            Some(pod.last_score)
+        } else {
+            None
+        }
+    }
+}
+
+impl FromStr for Race {
+    type Err = ParseError;
+
+    fn from_str(test_in: &str) -> Result<Self, Self::Err> {
+        Ok(Race::from_points(&parse_points(test_in)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::action::parse_actions;
+
+    #[test]
+    fn test_start_position() {
+        let track = load_testcase("testcases/test1.json").unwrap();
+        let pod = get_initial_pod(&track);
+
+        assert_eq!(pod.x, 10353.0);
+        assert_eq!(pod.y, 1986.0);
+        assert_eq!(pod.angle.round(), 161.0);
+        assert_eq!(pod.vx, 0.0);
+        assert_eq!(pod.vy, 0.0);
+        assert_eq!(pod.next_checkpoint_id, 0);
+    }
+
+    #[test]
+    fn test_checkpoint_list() {
+        let track = load_testcase("testcases/test1.json").unwrap();
+
+        let expected = Track::new(
+            vec![
+                CheckPoint::from_i32(2757, 4659),
+                CheckPoint::from_i32(3358, 2838),
+                CheckPoint::from_i32(10353, 1986),
+            ],
+            3,
+        );
+
+        assert_eq!(track, expected);
+    }
+
+    #[test]
+    fn test_apply_move() {
+        let track = Track::new(
+            vec![CheckPoint::from_i32(800, 0), CheckPoint::from_i32(2200, 0)],
+            1,
+        );
+        let mut pod = get_initial_pod(&track);
+
+        assert_eq!(pod.turn, 0);
+
+        pod.apply_move(&Action::new(1, 0), &track);
+        assert_eq!(pod.turn, 1);
+        assert!(!pod.done);
+
+        for _ in 0..598 {
+            pod.apply_move(&Action::new(1, 0), &track);
+        }
+        assert_eq!(pod.turn, 599);
+        assert!(!pod.done);
+
+        pod.apply_move(&Action::new(1, 0), &track);
+        assert_eq!(pod.turn, 600);
+        assert!(pod.done);
+    }
+
+    #[test]
+    fn test_apply_moves() {
+        let track = Track::new(
+            vec![CheckPoint::from_i32(800, 0), CheckPoint::from_i32(2200, 0)],
+            1,
+        );
+        let mut pod = get_initial_pod(&track);
+
+        assert_eq!(pod.turn, 0);
+
+        pod.apply_move(&Action::new(1, 0), &track);
+        assert_eq!(pod.turn, 1);
+        assert!(!pod.done);
+
+        let actions: Vec<Action> = (0..598).map(|_| Action::new(1, 0)).collect();
+        pod.apply_moves(&actions, &track);
+        assert_eq!(pod.turn, 599);
+        assert!(!pod.done);
+
+        pod.apply_move(&Action::new(1, 0), &track);
+        assert_eq!(pod.turn, 600);
+        assert!(pod.done);
+    }
+
+    #[test]
+    fn test_simulation_1() {
+        let track = load_testcase("testcases/test13.json").unwrap();
+        let mut pod = get_initial_pod(&track);
+
+        assert_eq!(pod.x, 13332.0);
+        assert_eq!(pod.y, 4114.0);
+        assert_eq!(pod.angle.round(), 154.0);
+        assert_eq!(pod.vx, 0.0);
+        assert_eq!(pod.vy, 0.0);
+        assert_eq!(pod.next_checkpoint_id, 0);
+
+        let command = "200,-14;200,-1;200,14;200,7;200,-12;176,0;200,18;116,18;100,18;0,18;0,18;15,18;146,18;200,18;200,18;195,18;200,9;200,6;200,15;200,15;200,11;200,11;161,-3;191,18;98,18;18,18;0,18;20,18;87,11;200,18;197,18;200,18;200,18;200,14;151,-7;166,9;200,-8;200,10;197,-1;200,7;200,-7;200,-18;200,-18;123,-18;7,-18;0,-18;0,-18;44,-18;58,-18;158,-18;176,-18;200,-18;200,-18;200,-17;200,-15;200,-2;200,0;200,9;198,-9;184,17;184,8;173,18;157,18;120,12;0,18;178,18;131,16;200,15;200,18;200,15;200,11;200,-9;200,9;200,4;194,10;200,14;171,16;200,15;16,18;53,18;0,18;200,18;200,15;200,18;194,18;200,12;200,14;176,8;143,15;188,18;192,-18;200,1;157,-18;11,-18;13,-18;0,-18;0,-18;3,-18;200,-18;200,-18;200,-16;200,-18;200,-3;200,-11;200,-18;200,6;200,10;200,2;200,-4;191,0;200,-18;200,-18;195,-18;37,-16;27,-18;0,-18;22,-18;5,-18;103,-18;200,-18;197,-9;200,-8;200,-6;200,-18;200,2;200,-5;200,6;194,11;200,18;200,8;200,18;149,9;91,18;64,14;114,13;160,18;200,18;200,18;200,11;190,-13;200,18;200,11;200,3;187,-4;200,18;198,18;92,18;90,18;12,18;174,18;199,18;200,18;200,14;200,18;200,14;200,3;200,18;199,1;200,15;200,5;193,15;92,18;0,18;10,18;0,18;43,18;200,18;200,18;200,18;200,11;200,0;198,0;200,18;200,-6;200,11;200,-11;200,-9;185,-10;198,-8;176,-12;37,-18;9,-18;0,-18;0,-18;48,-18;145,-18;200,-18;200,-18;200,-18;200,-18;200,-18;200,-18;200,18;149,-17;199,7;71,-10;179,5;169,18;157,18;139,10;185,16;187,16;176,15;0,18;200,18;200,18;200,0;200,-8";
+
+        let actions = parse_actions(command).unwrap();
+
+        pod.apply_moves(&actions, &track);
+        if pod.done {
+            let error_score = pod.last_score - 207.57;
+            assert!(error_score.abs() < 0.1, "Error score: {}", error_score);
+            return;
+        }
+
+        panic!("Game should have ended before the last action");
+    }
+
+    #[test]
+    fn test_import_commands() {
+        let race = Race::load("testcases/test1.json").unwrap();
+        let mut pod = race.initial_pod();
+        let mut log = String::new();
+        for _ in 0..5 {
+            let command = pod.command(&Action::new(200, 5));
+            log.push_str(&format!("{}\n", command));
+            race.step(&mut pod, &Action::new(200, 5));
+        }
+
+        let actions = race.import_commands(&log).unwrap();
+        assert_eq!(actions, vec![Action::new(200, 5); 5]);
+        assert!(race.import_commands("1 2").is_err());
+    }
+
+    #[test]
+    fn test_race_score() {
+        let race = Race::load("testcases/test13.json").unwrap();
+        let actions: Vec<Action> = (0..10).map(|_| Action::new(200, 0)).collect();
+
+        let pod = race.simulate(&actions);
+        assert_eq!(pod.turn, 10);
+        assert!(!race.is_finished(&pod));
+        assert_eq!(race.score(&actions), None);
+    }
+
+    #[test]
+    fn test_parse_map() {
+        let race: Race = "10353 1986;2757 4659;3358 2838".parse().unwrap();
+        assert_eq!(race.track, load_testcase("testcases/test1.json").unwrap());
+
+        assert_eq!(
+            "0 0;100 1O0".parse::<Race>().unwrap_err(),
+            ParseError::new("y", "1O0").at(1)
+        );
+        assert_eq!(
+            "0 0;100".parse::<Race>().unwrap_err(),
+            ParseError::new("y", "").at(1)
+        );
+        assert!("0 0".parse::<Race>().is_err());
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let error = Race::load("testcases/missing.json").unwrap_err();
+        assert!(matches!(error, Error::Io { .. }));
+        assert!(error.to_string().starts_with("testcases/missing.json"));
+
+        let dir = std::env::temp_dir().join(format!("search_race_maps_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let path = dir.join("broken.json");
+        fs::write(&path, "{\"testIn\": \"0 0;5000 x\"}").unwrap();
+        let error = Race::load(&path).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("{}: invalid y 'x' at position 1", path.display())
+        );
+
+        fs::write(&path, "{\"test\": 1}").unwrap();
+        assert!(matches!(Race::load(&path), Err(Error::Json { .. })));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,84 @@
+use std::fmt::Write;
+
+use crate::game::action::Action;
+use crate::race::Race;
+
+// Size of the Search Race map
+const WIDTH: i32 = 16000;
+const HEIGHT: i32 = 9000;
+
+pub fn svg(race: &Race, actions: &[Action]) -> String {
+    let mut out = String::new();
+    let n = race.track.len();
+
+    writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {} {}\">",
+        WIDTH, HEIGHT
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "<rect width=\"{}\" height=\"{}\" fill=\"#1e1e2e\"/>",
+        WIDTH, HEIGHT
+    )
+    .unwrap();
+
+    for (i, checkpoint) in race.track.checkpoints.iter().enumerate() {
+        // The start line is the last checkpoint of a lap, it is labelled 0 like in the game
+        let label = (i + 1) % n;
+        writeln!(
+            out,
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"none\" stroke=\"#89b4fa\" stroke-width=\"40\"/>",
+            checkpoint.x, checkpoint.y, checkpoint.r
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "<text x=\"{}\" y=\"{}\" fill=\"#89b4fa\" font-size=\"500\" text-anchor=\"middle\" dominant-baseline=\"middle\">{}</text>",
+            checkpoint.x, checkpoint.y, label
+        )
+        .unwrap();
+    }
+
+    if !actions.is_empty() {
+        let mut pod = race.initial_pod();
+        let mut points = vec![format!("{},{}", pod.x, pod.y)];
+        for action in actions {
+            if pod.done {
+                break;
+            }
+            race.step(&mut pod, action);
+            points.push(format!("{},{}", pod.x, pod.y));
+        }
+        writeln!(
+            out,
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"#f38ba8\" stroke-width=\"30\"/>",
+            points.join(" ")
+        )
+        .unwrap();
+    }
+
+    out.push_str("</svg>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_svg() {
+        let race = Race::load("testcases/test1.json").unwrap();
+
+        let map = svg(&race, &[]);
+        assert_eq!(map.matches("<circle").count(), 3);
+        assert!(!map.contains("<polyline"));
+
+        let actions = vec![Action::new(200, 0); 10];
+        let trajectory = svg(&race, &actions);
+        assert!(trajectory.contains("<polyline"));
+        assert!(trajectory.contains("10353,1986"));
+        assert!(trajectory.ends_with("</svg>\n"));
+    }
+}
@@ -0,0 +1,165 @@
+use crate::game::action::Action;
+use crate::game::pod::Pod;
+use crate::game::rules::GameRules;
+use crate::game::track::Track;
+use crate::search::rng::Rng;
+use crate::search::{Budget, GreedySearch, Search, SearchResult, evaluate};
+
+#[derive(Debug)]
+pub struct SimulatedAnnealing {
+    // Starting solution, the greedy output is used when empty
+    pub initial: Vec<Action>,
+    pub iterations: usize,
+    pub start_temperature: f64,
+    pub end_temperature: f64,
+    pub max_span: usize,
+    // Extra turns appended after the initial solution so that mutations may finish later
+    pub slack: usize,
+    pub seed: u64,
+}
+
+impl SimulatedAnnealing {
+    pub fn new(initial: Vec<Action>, iterations: usize, seed: u64) -> Self {
+        SimulatedAnnealing {
+            initial,
+            iterations,
+            start_temperature: 1.0,
+            end_temperature: 0.01,
+            max_span: 10,
+            slack: 30,
+            seed,
+        }
+    }
+
+    fn temperature(&self, iteration: usize) -> f64 {
+        let progress = iteration as f64 / self.iterations.max(1) as f64;
+        self.start_temperature * (self.end_temperature / self.start_temperature).powf(progress)
+    }
+
+    fn mutate(&self, actions: &mut [Action], rules: &GameRules, rng: &mut Rng) -> usize {
+        let start = rng.below(actions.len());
+        if rng.chance(0.5) {
+            // Single turn: either a small tweak or a brand new action
+            let action = &mut actions[start];
+            if rng.chance(0.5) {
+                let tweaked = Action::new(
+                    action.thrust + rng.range(-20, 20),
+                    action.angle + rng.range(-3, 3),
+                );
+                *action = rules.clamp(&tweaked);
+            } else {
+                *action = rng.action(rules);
+            }
+        } else {
+            // Span: the same shift is applied on several consecutive turns
+            let end = (start + 1 + rng.below(self.max_span.max(1))).min(actions.len());
+            let d_thrust = rng.range(-30, 30);
+            let d_angle = rng.range(-5, 5);
+            for action in actions[start..end].iter_mut() {
+                *action = rules.clamp(&Action::new(
+                    action.thrust + d_thrust,
+                    action.angle + d_angle,
+                ));
+            }
+        }
+        start
+    }
+
+    fn states(track: &Track, actions: &[Action], from: usize, states: &mut Vec<Pod>) {
+        // states[i] is the pod before actions[i] is played
+        states.truncate(from + 1);
+        let mut current = states[from].clone_pod();
+        for action in actions[from..].iter() {
+            if current.done {
+                break;
+            }
+            current.apply_move(action, track);
+            states.push(current.clone_pod());
+        }
+    }
+}
+
+impl Default for SimulatedAnnealing {
+    fn default() -> Self {
+        SimulatedAnnealing::new(Vec::new(), 100_000, 0)
+    }
+}
+
+impl Search for SimulatedAnnealing {
+    fn search(&mut self, track: &Track, pod: &Pod, budget: &mut Budget) -> SearchResult {
+        let mut rng = Rng::new(self.seed);
+
+        let mut current = if self.initial.is_empty() {
+            GreedySearch::default().search(track, pod, budget).actions
+        } else {
+            self.initial.clone()
+        };
+        if current.is_empty() {
+            return SearchResult::from_actions(track, pod, current);
+        }
+        let last = current[current.len() - 1];
+        current.extend((0..self.slack).map(|_| last));
+
+        let mut states = vec![pod.clone_pod()];
+        SimulatedAnnealing::states(track, &current, 0, &mut states);
+        let mut current_score = evaluate(&states[states.len() - 1], track);
+
+        let mut best = current.clone();
+        let mut best_score = current_score;
+
+        for iteration in 0..self.iterations {
+            if budget.is_exhausted() {
+                break;
+            }
+
+            let mut candidate = current.clone();
+            let start = self.mutate(&mut candidate, &track.rules, &mut rng);
+            if start >= states.len() {
+                // The mutation only touched turns played after the end of the race
+                continue;
+            }
+
+            let mut child = states[start].clone_pod();
+            child.apply_moves(&candidate[start..], track);
+            budget.consume(child.turn - states[start].turn);
+            let score = evaluate(&child, track);
+
+            let delta = score - current_score;
+            if delta >= 0.0 || rng.chance((delta / self.temperature(iteration)).exp()) {
+                current = candidate;
+                current_score = score;
+                SimulatedAnnealing::states(track, &current, start, &mut states);
+
+                if current_score > best_score {
+                    best_score = current_score;
+                    best = current.clone();
+                }
+            }
+        }
+
+        let mut end = pod.clone_pod();
+        end.apply_moves(&best, track);
+        best.truncate(end.turn - pod.turn);
+        SearchResult::from_actions(track, pod, best)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::race::Race;
+
+    #[test]
+    fn test_annealing_improves_greedy() {
+        let race = Race::load("testcases/test1.json").unwrap();
+        let pod = race.initial_pod();
+        let greedy = GreedySearch::default().search(&race.track, &pod, &mut Budget::unlimited());
+
+        let mut search = SimulatedAnnealing::new(greedy.actions.clone(), 2_000, 5);
+        let result = search.search(&race.track, &pod, &mut Budget::unlimited());
+
+        assert!(result.finished);
+        assert!(result.score <= greedy.score);
+        assert_eq!(race.score(&result.actions), Some(result.score));
+    }
+}
@@ -0,0 +1,178 @@
+use std::collections::HashSet;
+
+use crate::game::action::Action;
+use crate::game::pod::Pod;
+use crate::game::track::Track;
+use crate::search::{Budget, Candidates, Search, SearchResult};
+
+#[derive(Debug)]
+pub struct BeamSearch {
+    pub width: usize,
+    pub candidates: Candidates,
+}
+
+struct Candidate {
+    pod: Pod,
+    score: f64,
+    parent: Option<usize>,
+    action: Action,
+}
+
+impl BeamSearch {
+    pub fn new(width: usize, candidates: Candidates) -> Self {
+        BeamSearch { width, candidates }
+    }
+
+    fn state_key(pod: &Pod) -> (i64, i64, i64, i64, i64, usize) {
+        // After _end every field of the pod is an integer, so the key is exact
+        (
+            pod.x as i64,
+            pod.y as i64,
+            pod.vx as i64,
+            pod.vy as i64,
+            pod.angle as i64,
+            pod.next_checkpoint_id,
+        )
+    }
+
+    fn path(history: &[(Option<usize>, Action)], mut node: Option<usize>) -> Vec<Action> {
+        let mut actions = Vec::new();
+        while let Some(id) = node {
+            let (parent, action) = history[id];
+            actions.push(action);
+            node = parent;
+        }
+        actions.reverse();
+        actions
+    }
+}
+
+impl Default for BeamSearch {
+    fn default() -> Self {
+        BeamSearch::new(200, Candidates::Grid(5, 5))
+    }
+}
+
+impl Search for BeamSearch {
+    fn search(&mut self, track: &Track, pod: &Pod, budget: &mut Budget) -> SearchResult {
+        let actions = self.candidates.actions(&track.rules);
+        // history[i] = (parent, action) of every state that survived a pruning step
+        let mut history: Vec<(Option<usize>, Action)> = Vec::new();
+        let mut beam: Vec<(Pod, Option<usize>)> = vec![(pod.clone_pod(), None)];
+        let mut best_node: Option<usize> = None;
+
+        while !beam.is_empty() && !budget.is_exhausted() {
+            let mut children: Vec<Candidate> = Vec::with_capacity(beam.len() * actions.len());
+            for (parent_pod, parent) in beam.iter() {
+                // Wide beams can take long to expand, the deadline is also checked in between
+                if budget.is_exhausted() && !children.is_empty() {
+                    break;
+                }
+                for action in actions.iter() {
+                    let mut child = parent_pod.clone_pod();
+                    child.apply_move(action, track);
+                    let score = child.fitness(track);
+                    children.push(Candidate {
+                        pod: child,
+                        score,
+                        parent: *parent,
+                        action: *action,
+                    });
+                }
+                budget.consume(actions.len());
+            }
+
+            // The first turn where a pod crosses the finish line ends the search:
+            // any other finish would happen later and score worse
+            let winner = children
+                .iter()
+                .filter(|c| c.pod.is_finished(track))
+                .min_by(|a, b| a.pod.last_score.total_cmp(&b.pod.last_score));
+            if let Some(winner) = winner {
+                history.push((winner.parent, winner.action));
+                best_node = Some(history.len() - 1);
+                break;
+            }
+
+            children.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+            let mut seen = HashSet::new();
+            beam = Vec::with_capacity(self.width);
+            for child in children {
+                if beam.len() >= self.width {
+                    break;
+                }
+                if !seen.insert(BeamSearch::state_key(&child.pod)) {
+                    continue;
+                }
+                history.push((child.parent, child.action));
+                let id = history.len() - 1;
+                if beam.is_empty() {
+                    best_node = Some(id);
+                }
+                if !child.pod.done {
+                    beam.push((child.pod, Some(id)));
+                }
+            }
+        }
+
+        let actions = BeamSearch::path(&history, best_node);
+        SearchResult::from_actions(track, pod, actions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::race::Race;
+    use crate::search::GreedySearch;
+
+    #[test]
+    fn test_beam_finishes_race() {
+        let race = Race::load("testcases/test1.json").unwrap();
+        let pod = race.initial_pod();
+        let mut search = BeamSearch::new(20, Candidates::Grid(3, 3));
+
+        let result = search.search(&race.track, &pod, &mut Budget::unlimited());
+
+        assert!(result.finished);
+        assert_eq!(race.score(&result.actions), Some(result.score));
+    }
+
+    #[test]
+    fn test_beam_stops_at_deadline() {
+        let race = Race::load("testcases/test13.json").unwrap();
+        let pod = race.initial_pod();
+        let mut search = BeamSearch::new(100_000, Candidates::All);
+
+        let start = std::time::Instant::now();
+        let mut budget = Budget::time(std::time::Duration::from_millis(50));
+        let result = search.search(&race.track, &pod, &mut budget);
+
+        // The best partial path found so far is returned
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+        assert!(!result.finished);
+        assert!(!result.actions.is_empty());
+    }
+
+    #[test]
+    fn test_beam_width_one_matches_greedy() {
+        let race = Race::load("testcases/test1.json").unwrap();
+        let pod = race.initial_pod();
+        let candidates = Candidates::Grid(3, 3);
+
+        let mut beam = BeamSearch::new(1, candidates.clone());
+        let mut greedy = GreedySearch::new(candidates);
+        let beam_result = beam.search(&race.track, &pod, &mut Budget::unlimited());
+        let greedy_result = greedy.search(&race.track, &pod, &mut Budget::unlimited());
+
+        // Same trajectory as the greedy, except that the beam picks the earliest finishing move
+        let turns = greedy_result.actions.len();
+        assert_eq!(beam_result.actions.len(), turns);
+        assert_eq!(
+            beam_result.actions[..turns - 1],
+            greedy_result.actions[..turns - 1]
+        );
+        assert!(beam_result.score <= greedy_result.score);
+    }
+}
@@ -0,0 +1,252 @@
+use crate::game::action::Action;
+use crate::game::pod::Pod;
+use crate::game::rules::GameRules;
+use crate::game::track::Track;
+use crate::search::rng::Rng;
+use crate::search::{Budget, Planner, Search, SearchResult, evaluate, play};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CemMode {
+    // One distribution per turn of the whole race, optimized once
+    Offline,
+    // Receding horizon: the distributions are refitted every turn and shifted after each move
+    Online,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Gaussian {
+    mean: f64,
+    std: f64,
+}
+
+impl Gaussian {
+    fn update(&mut self, values: &[f64], smoothing: f64, min_std: f64) {
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        let std = (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt();
+
+        self.mean = smoothing * mean + (1.0 - smoothing) * self.mean;
+        self.std = (smoothing * std + (1.0 - smoothing) * self.std).max(min_std);
+    }
+}
+
+#[derive(Debug)]
+pub struct CrossEntropy {
+    pub mode: CemMode,
+    // Planning horizon of the online mode, the offline mode plans until the turn limit
+    pub horizon: usize,
+    pub samples: usize,
+    pub elite_fraction: f64,
+    pub iterations: usize,
+    pub thrust_std: f64,
+    pub angle_std: f64,
+    pub min_std: f64,
+    // Weight of the refitted distribution against the previous one
+    pub smoothing: f64,
+    rng: Rng,
+    distributions: Vec<(Gaussian, Gaussian)>,
+    // State the distributions were shifted for in online mode
+    expected: Option<Pod>,
+}
+
+impl CrossEntropy {
+    pub fn new(
+        mode: CemMode,
+        horizon: usize,
+        samples: usize,
+        iterations: usize,
+        seed: u64,
+    ) -> Self {
+        CrossEntropy {
+            mode,
+            horizon,
+            samples,
+            elite_fraction: 0.1,
+            iterations,
+            thrust_std: 60.0,
+            angle_std: 12.0,
+            min_std: 1.0,
+            smoothing: 0.7,
+            rng: Rng::new(seed),
+            distributions: Vec::new(),
+            expected: None,
+        }
+    }
+
+    fn prior(&self, rules: &GameRules) -> (Gaussian, Gaussian) {
+        (
+            Gaussian {
+                mean: rules.max_thrust as f64,
+                std: self.thrust_std,
+            },
+            Gaussian {
+                mean: 0.0,
+                std: self.angle_std,
+            },
+        )
+    }
+
+    fn sample(&mut self, rules: &GameRules) -> Vec<Action> {
+        let mut actions = Vec::with_capacity(self.distributions.len());
+        for i in 0..self.distributions.len() {
+            let (thrust, angle) = self.distributions[i];
+            let t = thrust.mean + thrust.std * self.rng.gaussian();
+            let a = angle.mean + angle.std * self.rng.gaussian();
+            actions.push(rules.clamp(&Action::new(t.round() as i32, a.round() as i32)));
+        }
+        actions
+    }
+
+    fn refit(&mut self, elites: &[(f64, Vec<Action>)]) {
+        let (smoothing, min_std) = (self.smoothing, self.min_std);
+        for (turn, (thrust, angle)) in self.distributions.iter_mut().enumerate() {
+            let thrusts: Vec<f64> = elites.iter().map(|(_, e)| e[turn].thrust as f64).collect();
+            let angles: Vec<f64> = elites.iter().map(|(_, e)| e[turn].angle as f64).collect();
+            thrust.update(&thrusts, smoothing, min_std);
+            angle.update(&angles, smoothing, min_std);
+        }
+    }
+
+    fn optimize(
+        &mut self,
+        horizon: usize,
+        track: &Track,
+        pod: &Pod,
+        budget: &mut Budget,
+    ) -> Vec<Action> {
+        let prior = self.prior(&track.rules);
+        self.distributions.resize(horizon, prior);
+
+        // The elites are taken from the samples, there has to be at least one of each
+        let samples = self.samples.max(1);
+        let n_elites = ((samples as f64 * self.elite_fraction).ceil() as usize).clamp(1, samples);
+        let mut best: (f64, Vec<Action>) = (f64::NEG_INFINITY, Vec::new());
+
+        for _ in 0..self.iterations {
+            if budget.is_exhausted() && !best.1.is_empty() {
+                break;
+            }
+
+            let mut population: Vec<(f64, Vec<Action>)> = Vec::with_capacity(samples);
+            for _ in 0..samples {
+                let actions = self.sample(&track.rules);
+                let mut child = pod.clone_pod();
+                child.apply_moves(&actions, track);
+                budget.consume(child.turn - pod.turn);
+                population.push((evaluate(&child, track), actions));
+            }
+            population.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+            if population[0].0 > best.0 {
+                best = (population[0].0, population[0].1.clone());
+            }
+            self.refit(&population[..n_elites]);
+        }
+
+        best.1
+    }
+}
+
+impl Default for CrossEntropy {
+    fn default() -> Self {
+        CrossEntropy::new(CemMode::Online, 15, 50, 10, 0)
+    }
+}
+
+impl Planner for CrossEntropy {
+    fn next_action(&mut self, track: &Track, pod: &Pod, budget: &mut Budget) -> Action {
+        // The shifted distributions only make sense from the state they were planned for
+        if self
+            .expected
+            .take()
+            .is_some_and(|expected| !expected.same_state(pod))
+        {
+            self.distributions.clear();
+        }
+
+        let plan = self.optimize(self.horizon, track, pod, budget);
+
+        // Shift the distributions: the plan for the next turns becomes the new starting point
+        if !self.distributions.is_empty() {
+            self.distributions.remove(0);
+        }
+
+        let action = plan
+            .first()
+            .copied()
+            .unwrap_or(Action::new(track.rules.max_thrust, 0));
+        let mut expected = pod.clone_pod();
+        expected.apply_move(&action, track);
+        self.expected = Some(expected);
+
+        action
+    }
+}
+
+impl Search for CrossEntropy {
+    fn search(&mut self, track: &Track, pod: &Pod, budget: &mut Budget) -> SearchResult {
+        match self.mode {
+            CemMode::Offline => {
+                self.distributions.clear();
+                self.expected = None;
+                let horizon = track.rules.max_turns - pod.turn;
+                let mut actions = self.optimize(horizon, track, pod, budget);
+
+                let mut end = pod.clone_pod();
+                end.apply_moves(&actions, track);
+                actions.truncate(end.turn - pod.turn);
+                SearchResult::from_actions(track, pod, actions)
+            }
+            CemMode::Online => play(self, track, pod, budget),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::race::Race;
+
+    #[test]
+    fn test_online_shift() {
+        let race = Race::load("testcases/test1.json").unwrap();
+        let pod = race.initial_pod();
+        let mut planner = CrossEntropy::new(CemMode::Online, 8, 10, 2, 0);
+
+        planner.next_action(&race.track, &pod, &mut Budget::unlimited());
+
+        // The distribution of the played turn is dropped, the next call appends a new one
+        assert_eq!(planner.distributions.len(), 7);
+    }
+
+    #[test]
+    fn test_degenerate_samples() {
+        let race = Race::load("testcases/test1.json").unwrap();
+        let pod = race.initial_pod();
+
+        // No samples and more elites than samples both fall back to something usable
+        for (samples, elite_fraction) in [(0, 0.1), (5, 3.0)] {
+            let mut planner = CrossEntropy {
+                elite_fraction,
+                ..CrossEntropy::new(CemMode::Online, 8, samples, 2, 0)
+            };
+            let action = planner.next_action(&race.track, &pod, &mut Budget::unlimited());
+            assert!(race.track.rules.allows(&action));
+        }
+    }
+
+    #[test]
+    fn test_cem_modes() {
+        let race = Race::load("testcases/test1.json").unwrap();
+        let pod = race.initial_pod();
+
+        for mode in [CemMode::Online, CemMode::Offline] {
+            let mut search = CrossEntropy::new(mode, 10, 20, 3, 1);
+            let result = search.search(&race.track, &pod, &mut Budget::unlimited());
+
+            let replayed = race.simulate(&result.actions);
+            assert_eq!(replayed.last_score, result.score);
+            assert_eq!(race.is_finished(&replayed), result.finished);
+        }
+    }
+}
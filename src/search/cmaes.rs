@@ -0,0 +1,230 @@
+use crate::game::action::Action;
+use crate::game::pod::Pod;
+use crate::game::rules::GameRules;
+use crate::game::track::Track;
+use crate::search::rng::Rng;
+use crate::search::{Budget, Search, SearchResult, evaluate};
+
+// Separable CMA-ES (Ros & Hansen, 2008): only the diagonal of the covariance is adapted, which
+// keeps every generation linear in the dimension (2 values per turn of the horizon).
+#[derive(Debug)]
+pub struct CmaEs {
+    // Starting mean, padded with full thrust straight ahead up to the horizon
+    pub initial: Vec<Action>,
+    pub horizon: usize,
+    // Offspring per generation, 4 + 3 ln(n) when None
+    pub population: Option<usize>,
+    pub generations: usize,
+    // Initial step size in scaled units, a few hundredths is enough to polish an existing solution
+    pub sigma: f64,
+    pub seed: u64,
+}
+
+impl CmaEs {
+    pub fn new(horizon: usize, generations: usize, seed: u64) -> Self {
+        CmaEs {
+            initial: Vec::new(),
+            horizon,
+            population: None,
+            generations,
+            sigma: 0.3,
+            seed,
+        }
+    }
+
+    // Thrust is scaled to [0, 1] and angle to [-1, 1] so that one sigma fits both
+    fn encode(actions: &[Action], rules: &GameRules) -> Vec<f64> {
+        let (thrust, rotation) = (rules.max_thrust as f64, rules.max_rotation as f64);
+        actions
+            .iter()
+            .flat_map(|action| {
+                [
+                    action.thrust as f64 / thrust,
+                    action.angle as f64 / rotation,
+                ]
+            })
+            .collect()
+    }
+
+    fn decode(x: &[f64], rules: &GameRules) -> Vec<Action> {
+        let (thrust, rotation) = (rules.max_thrust as f64, rules.max_rotation as f64);
+        x.chunks(2)
+            .map(|gene| {
+                let action = Action::new(
+                    (gene[0] * thrust).round() as i32,
+                    (gene[1] * rotation).round() as i32,
+                );
+                rules.clamp(&action)
+            })
+            .collect()
+    }
+}
+
+impl Default for CmaEs {
+    fn default() -> Self {
+        CmaEs::new(300, 500, 0)
+    }
+}
+
+impl Search for CmaEs {
+    fn search(&mut self, track: &Track, pod: &Pod, budget: &mut Budget) -> SearchResult {
+        let mut rng = Rng::new(self.seed);
+
+        let mut start = self.initial.clone();
+        start.resize(self.horizon, Action::new(track.rules.max_thrust, 0));
+        let mut mean = CmaEs::encode(&start, &track.rules);
+        let n = mean.len();
+        let nf = n as f64;
+
+        let lambda = self
+            .population
+            .unwrap_or(4 + (3.0 * nf.ln()).floor() as usize);
+        let mu = lambda / 2;
+        let raw: Vec<f64> = (0..mu)
+            .map(|i| (mu as f64 + 0.5).ln() - ((i + 1) as f64).ln())
+            .collect();
+        let sum: f64 = raw.iter().sum();
+        let weights: Vec<f64> = raw.iter().map(|w| w / sum).collect();
+        let mueff = 1.0 / weights.iter().map(|w| w * w).sum::<f64>();
+
+        let cs = (mueff + 2.0) / (nf + mueff + 5.0);
+        let ds = 1.0 + 2.0 * (((mueff - 1.0) / (nf + 1.0)).sqrt() - 1.0).max(0.0) + cs;
+        let cc = (4.0 + mueff / nf) / (nf + 4.0 + 2.0 * mueff / nf);
+        let c1 = 2.0 / ((nf + 1.3).powi(2) + mueff);
+        let cmu = (1.0 - c1).min(2.0 * (mueff - 2.0 + 1.0 / mueff) / ((nf + 2.0).powi(2) + mueff));
+        // The separable variant can afford a faster learning rate
+        let c1 = c1 * (nf + 2.0) / 3.0;
+        let cmu = (cmu * (nf + 2.0) / 3.0).min(1.0 - c1);
+        let chi_n = nf.sqrt() * (1.0 - 1.0 / (4.0 * nf) + 1.0 / (21.0 * nf * nf));
+
+        let mut sigma = self.sigma;
+        let mut diag: Vec<f64> = vec![1.0; n];
+        let mut ps = vec![0.0; n];
+        let mut pc = vec![0.0; n];
+
+        let mut best = start;
+        let mut best_score = {
+            let mut child = pod.clone_pod();
+            child.apply_moves(&best, track);
+            budget.consume(child.turn - pod.turn);
+            evaluate(&child, track)
+        };
+
+        for generation in 0..self.generations {
+            if budget.is_exhausted() {
+                break;
+            }
+
+            // (score, z, y) for every offspring
+            let mut offspring: Vec<(f64, Vec<f64>, Vec<f64>)> = Vec::with_capacity(lambda);
+            for _ in 0..lambda {
+                let z: Vec<f64> = (0..n).map(|_| rng.gaussian()).collect();
+                let y: Vec<f64> = z
+                    .iter()
+                    .zip(diag.iter())
+                    .map(|(z, c)| z * c.sqrt())
+                    .collect();
+                let x: Vec<f64> = mean
+                    .iter()
+                    .zip(y.iter())
+                    .map(|(m, y)| m + sigma * y)
+                    .collect();
+
+                let actions = CmaEs::decode(&x, &track.rules);
+                let mut child = pod.clone_pod();
+                child.apply_moves(&actions, track);
+                budget.consume(child.turn - pod.turn);
+                let score = evaluate(&child, track);
+
+                if score > best_score {
+                    best_score = score;
+                    best = actions;
+                }
+                offspring.push((score, z, y));
+            }
+            offspring.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+            let mut y_w = vec![0.0; n];
+            let mut z_w = vec![0.0; n];
+            for (w, (_, z, y)) in weights.iter().zip(offspring.iter()) {
+                for j in 0..n {
+                    y_w[j] += w * y[j];
+                    z_w[j] += w * z[j];
+                }
+            }
+
+            for j in 0..n {
+                mean[j] += sigma * y_w[j];
+                // With a diagonal covariance, C^-1/2 * y_w is simply z_w
+                ps[j] = (1.0 - cs) * ps[j] + (cs * (2.0 - cs) * mueff).sqrt() * z_w[j];
+            }
+
+            let ps_norm = ps.iter().map(|p| p * p).sum::<f64>().sqrt();
+            let correction = (1.0 - (1.0 - cs).powi(2 * (generation as i32 + 1))).sqrt();
+            let hsig = ps_norm / correction / chi_n < 1.4 + 2.0 / (nf + 1.0);
+            let hsig = if hsig { 1.0 } else { 0.0 };
+
+            for j in 0..n {
+                pc[j] = (1.0 - cc) * pc[j] + hsig * (cc * (2.0 - cc) * mueff).sqrt() * y_w[j];
+
+                let rank_mu: f64 = weights
+                    .iter()
+                    .zip(offspring.iter())
+                    .map(|(w, (_, _, y))| w * y[j] * y[j])
+                    .sum();
+                diag[j] = (1.0 - c1 - cmu) * diag[j]
+                    + c1 * (pc[j] * pc[j] + (1.0 - hsig) * cc * (2.0 - cc) * diag[j])
+                    + cmu * rank_mu;
+            }
+
+            sigma *= ((cs / ds) * (ps_norm / chi_n - 1.0)).exp();
+        }
+
+        let mut end = pod.clone_pod();
+        end.apply_moves(&best, track);
+        best.truncate(end.turn - pod.turn);
+        SearchResult::from_actions(track, pod, best)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::race::Race;
+    use crate::search::GreedySearch;
+
+    #[test]
+    fn test_encode_decode() {
+        let actions = vec![
+            Action::new(200, -18),
+            Action::new(0, 18),
+            Action::new(57, 3),
+        ];
+        let rules = GameRules::default();
+        assert_eq!(
+            CmaEs::decode(&CmaEs::encode(&actions, &rules), &rules),
+            actions
+        );
+
+        // Out of range values are clamped
+        assert_eq!(
+            CmaEs::decode(&[1.7, -3.0], &rules),
+            vec![Action::new(200, -18)]
+        );
+    }
+
+    #[test]
+    fn test_cmaes_never_worse_than_initial() {
+        let race = Race::load("testcases/test1.json").unwrap();
+        let pod = race.initial_pod();
+        let greedy = GreedySearch::default().search(&race.track, &pod, &mut Budget::unlimited());
+
+        let mut search = CmaEs::new(greedy.actions.len() + 10, 5, 4);
+        search.initial = greedy.actions.clone();
+        let result = search.search(&race.track, &pod, &mut Budget::unlimited());
+
+        assert!(result.finished);
+        assert!(result.score <= greedy.score);
+        assert_eq!(race.score(&result.actions), Some(result.score));
+    }
+}
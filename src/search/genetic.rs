@@ -0,0 +1,209 @@
+use crate::game::action::Action;
+use crate::game::pod::Pod;
+use crate::game::rules::GameRules;
+use crate::game::track::Track;
+use crate::search::rng::Rng;
+use crate::search::{Budget, Search, SearchResult, evaluate};
+
+#[derive(Debug)]
+pub struct GeneticSearch {
+    pub horizon: usize,
+    pub population: usize,
+    pub generations: usize,
+    pub elites: usize,
+    pub tournament: usize,
+    pub crossover_rate: f64,
+    pub mutation_rate: f64,
+    pub seed: u64,
+    // Optional genomes injected in the first generation (e.g. a greedy or beam solution)
+    pub seeds: Vec<Vec<Action>>,
+}
+
+struct Individual {
+    genome: Vec<Action>,
+    score: f64,
+    turns: usize,
+}
+
+impl GeneticSearch {
+    pub fn new(horizon: usize, population: usize, generations: usize, seed: u64) -> Self {
+        GeneticSearch {
+            horizon,
+            population,
+            generations,
+            elites: 2,
+            tournament: 3,
+            crossover_rate: 0.9,
+            mutation_rate: 0.02,
+            seed,
+            seeds: Vec::new(),
+        }
+    }
+
+    fn random_genome(&self, rules: &GameRules, rng: &mut Rng) -> Vec<Action> {
+        (0..self.horizon).map(|_| rng.action(rules)).collect()
+    }
+
+    fn evaluate(genome: Vec<Action>, track: &Track, pod: &Pod, budget: &mut Budget) -> Individual {
+        let mut child = pod.clone_pod();
+        child.apply_moves(&genome, track);
+        let turns = child.turn - pod.turn;
+        budget.consume(turns);
+
+        Individual {
+            genome,
+            score: evaluate(&child, track),
+            turns,
+        }
+    }
+
+    fn select<'a>(&self, population: &'a [Individual], rng: &mut Rng) -> &'a Individual {
+        let mut best = &population[rng.below(population.len())];
+        // At least one contestant, a tournament of 0 would still pick one at random
+        for _ in 1..self.tournament.max(1) {
+            let challenger = &population[rng.below(population.len())];
+            if challenger.score > best.score {
+                best = challenger;
+            }
+        }
+        best
+    }
+
+    fn crossover(&self, a: &[Action], b: &[Action], rng: &mut Rng) -> Vec<Action> {
+        if !rng.chance(self.crossover_rate) {
+            return a.to_vec();
+        }
+
+        // Single point crossover keeps the beginning of the race from the first parent
+        let cut = rng.below(a.len() + 1);
+        a[..cut].iter().chain(b[cut..].iter()).copied().collect()
+    }
+
+    fn mutate(&self, genome: &mut [Action], rules: &GameRules, rng: &mut Rng) {
+        for gene in genome.iter_mut() {
+            if rng.chance(self.mutation_rate) {
+                if rng.chance(0.5) {
+                    gene.thrust = rng.range(0, rules.max_thrust);
+                } else {
+                    gene.thrust = (gene.thrust + rng.range(-50, 50)).clamp(0, rules.max_thrust);
+                }
+            }
+            if rng.chance(self.mutation_rate) {
+                gene.angle = rng.range(-rules.max_rotation, rules.max_rotation);
+            }
+        }
+    }
+}
+
+impl Default for GeneticSearch {
+    fn default() -> Self {
+        GeneticSearch::new(600, 50, 200, 0)
+    }
+}
+
+impl Search for GeneticSearch {
+    fn search(&mut self, track: &Track, pod: &Pod, budget: &mut Budget) -> SearchResult {
+        let mut rng = Rng::new(self.seed);
+
+        // Selection draws from the population, it can never be empty
+        let size = self.population.max(1);
+        let mut population: Vec<Individual> = Vec::with_capacity(size);
+        for i in 0..size {
+            if budget.is_exhausted() && !population.is_empty() {
+                break;
+            }
+            let mut genome = match self.seeds.get(i) {
+                Some(seed) => seed.clone(),
+                None => self.random_genome(&track.rules, &mut rng),
+            };
+            // Short seeds are completed at random, long ones are cut to the horizon
+            genome.resize_with(self.horizon, || rng.action(&track.rules));
+            population.push(GeneticSearch::evaluate(genome, track, pod, budget));
+        }
+
+        for _ in 0..self.generations {
+            if budget.is_exhausted() {
+                break;
+            }
+
+            population.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+            let mut next: Vec<Individual> = Vec::with_capacity(size);
+            for elite in population.iter().take(self.elites) {
+                next.push(Individual {
+                    genome: elite.genome.clone(),
+                    score: elite.score,
+                    turns: elite.turns,
+                });
+            }
+
+            while next.len() < size {
+                let a = self.select(&population, &mut rng);
+                let b = self.select(&population, &mut rng);
+                let mut genome = self.crossover(&a.genome, &b.genome, &mut rng);
+                self.mutate(&mut genome, &track.rules, &mut rng);
+                next.push(GeneticSearch::evaluate(genome, track, pod, budget));
+            }
+
+            population = next;
+        }
+
+        let best = population
+            .into_iter()
+            .max_by(|a, b| a.score.total_cmp(&b.score))
+            .expect("population should not be empty");
+
+        let mut actions = best.genome;
+        actions.truncate(best.turns);
+        SearchResult::from_actions(track, pod, actions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::race::Race;
+    use crate::search::GreedySearch;
+
+    #[test]
+    fn test_genetic_is_deterministic() {
+        let race = Race::load("testcases/test1.json").unwrap();
+        let pod = race.initial_pod();
+
+        let mut a = GeneticSearch::new(50, 10, 5, 7);
+        let mut b = GeneticSearch::new(50, 10, 5, 7);
+        let result_a = a.search(&race.track, &pod, &mut Budget::unlimited());
+        let result_b = b.search(&race.track, &pod, &mut Budget::unlimited());
+
+        assert_eq!(result_a.actions, result_b.actions);
+    }
+
+    #[test]
+    fn test_empty_population() {
+        let race = Race::load("testcases/test1.json").unwrap();
+        let pod = race.initial_pod();
+        let mut search = GeneticSearch {
+            tournament: 0,
+            ..GeneticSearch::new(50, 0, 5, 7)
+        };
+
+        let result = search.search(&race.track, &pod, &mut Budget::unlimited());
+        assert_eq!(result.actions.len(), 50);
+    }
+
+    #[test]
+    fn test_genetic_keeps_seeded_solution() {
+        let race = Race::load("testcases/test1.json").unwrap();
+        let pod = race.initial_pod();
+        let greedy = GreedySearch::default().search(&race.track, &pod, &mut Budget::unlimited());
+
+        let mut search = GeneticSearch::new(300, 10, 5, 1);
+        search.seeds.push(greedy.actions.clone());
+        let result = search.search(&race.track, &pod, &mut Budget::unlimited());
+
+        // Elitism: the population can only improve on the injected greedy run
+        assert!(result.finished);
+        assert!(result.score <= greedy.score);
+        assert_eq!(race.score(&result.actions), Some(result.score));
+    }
+}
@@ -0,0 +1,98 @@
+use crate::game::action::Action;
+use crate::game::pod::Pod;
+use crate::game::track::Track;
+use crate::search::{Budget, Candidates, Planner, Search, SearchResult};
+
+#[derive(Debug)]
+pub struct GreedySearch {
+    pub candidates: Candidates,
+}
+
+impl GreedySearch {
+    pub fn new(candidates: Candidates) -> Self {
+        GreedySearch { candidates }
+    }
+
+    pub fn best_action(actions: &[Action], track: &Track, base_pod: &Pod) -> Action {
+        // One-ply search: the action which brings the pod closest to its next checkpoint
+        let mut best_score = 0.0;
+        let mut best_action = actions[0];
+        for action in actions.iter() {
+            let mut pod = base_pod.clone_pod();
+            pod.apply_move(action, track);
+            let score = pod.fitness(track);
+
+            if score > best_score {
+                best_score = score;
+                best_action = *action;
+            }
+        }
+        best_action
+    }
+}
+
+impl Default for GreedySearch {
+    fn default() -> Self {
+        GreedySearch::new(Candidates::All)
+    }
+}
+
+impl Planner for GreedySearch {
+    fn next_action(&mut self, track: &Track, pod: &Pod, budget: &mut Budget) -> Action {
+        let actions = self.candidates.actions(&track.rules);
+        budget.consume(actions.len());
+        GreedySearch::best_action(&actions, track, pod)
+    }
+}
+
+impl Search for GreedySearch {
+    fn search(&mut self, track: &Track, pod: &Pod, budget: &mut Budget) -> SearchResult {
+        let actions = self.candidates.actions(&track.rules);
+        let mut base_pod = pod.clone_pod();
+        let mut all_actions: Vec<Action> = Vec::new();
+        while !base_pod.done && !budget.is_exhausted() {
+            let best_action = GreedySearch::best_action(&actions, track, &base_pod);
+            budget.consume(actions.len());
+
+            base_pod.apply_move(&best_action, track);
+            all_actions.push(best_action);
+        }
+
+        SearchResult::from_actions(track, pod, all_actions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::race::Race;
+
+    #[test]
+    fn test_greedy_finishes_race() {
+        let race = Race::load("testcases/test1.json").unwrap();
+        let pod = race.initial_pod();
+        let mut search = GreedySearch::default();
+
+        let result = search.search(&race.track, &pod, &mut Budget::unlimited());
+
+        assert!(result.finished);
+        assert_eq!(race.score(&result.actions), Some(result.score));
+    }
+
+    #[test]
+    fn test_greedy_respects_budget() {
+        let race = Race::load("testcases/test1.json").unwrap();
+        let pod = race.initial_pod();
+        let mut search = GreedySearch::new(Candidates::Fixed(vec![
+            Action::new(200, 0),
+            Action::new(0, 0),
+        ]));
+        let mut budget = Budget::evaluations(10);
+
+        let result = search.search(&race.track, &pod, &mut budget);
+
+        assert_eq!(result.actions.len(), 5);
+        assert!(!result.finished);
+        assert!(budget.is_exhausted());
+    }
+}
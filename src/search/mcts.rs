@@ -0,0 +1,315 @@
+use crate::game::action::Action;
+use crate::game::pod::Pod;
+use crate::game::track::Track;
+use crate::search::rng::Rng;
+use crate::search::{Budget, Planner, Search, SearchResult, evaluate, play};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rollout {
+    // The leaf is valued directly with its fitness
+    Fitness,
+    // Random actions are played for the given number of turns before the evaluation
+    Random(usize),
+}
+
+#[derive(Debug)]
+struct Node {
+    pod: Pod,
+    action: Action,
+    children: Vec<usize>,
+    visits: u32,
+    total: f64,
+    depth: usize,
+}
+
+#[derive(Debug)]
+pub struct MonteCarloTreeSearch {
+    pub iterations: usize,
+    pub exploration: f64,
+    // Progressive widening: a node visited n times may have ceil(widening * n^widening_exponent) children
+    pub widening: f64,
+    pub widening_exponent: f64,
+    pub max_depth: usize,
+    pub rollout: Rollout,
+    rng: Rng,
+    nodes: Vec<Node>,
+    // Child of the root played on the previous turn, kept to reuse its subtree
+    played: Option<usize>,
+    min_value: f64,
+    max_value: f64,
+}
+
+impl MonteCarloTreeSearch {
+    pub fn new(iterations: usize, max_depth: usize, rollout: Rollout, seed: u64) -> Self {
+        MonteCarloTreeSearch {
+            iterations,
+            exploration: 0.4,
+            widening: 2.0,
+            widening_exponent: 0.5,
+            max_depth,
+            rollout,
+            rng: Rng::new(seed),
+            nodes: Vec::new(),
+            played: None,
+            min_value: f64::INFINITY,
+            max_value: f64::NEG_INFINITY,
+        }
+    }
+
+    fn reset(&mut self, pod: &Pod) {
+        self.nodes.clear();
+        self.nodes.push(Node {
+            pod: pod.clone_pod(),
+            action: Action::new(0, 0),
+            children: Vec::new(),
+            visits: 0,
+            total: 0.0,
+            depth: 0,
+        });
+        self.min_value = f64::INFINITY;
+        self.max_value = f64::NEG_INFINITY;
+    }
+
+    fn reroot(&mut self, id: usize) {
+        // Only the subtree of the new root is kept, the nodes are copied in a new arena
+        let old_nodes = std::mem::take(&mut self.nodes);
+        let mut old_nodes: Vec<Option<Node>> = old_nodes.into_iter().map(Some).collect();
+        let root_depth = old_nodes[id].as_ref().unwrap().depth;
+
+        let mut queue = std::collections::VecDeque::from([id]);
+        let mut new_ids = vec![usize::MAX; old_nodes.len()];
+        new_ids[id] = 0;
+        let mut next_id = 1;
+        while let Some(old_id) = queue.pop_front() {
+            // Nodes are pushed in the order they are queued, which gives their new ids
+            let mut node = old_nodes[old_id].take().unwrap();
+            for &child in node.children.iter() {
+                new_ids[child] = next_id;
+                next_id += 1;
+                queue.push_back(child);
+            }
+            node.depth -= root_depth;
+            self.nodes.push(node);
+        }
+
+        for node in self.nodes.iter_mut() {
+            for child in node.children.iter_mut() {
+                *child = new_ids[*child];
+            }
+        }
+
+        // Values keep growing along the race, the old bounds would squash the new ones
+        self.min_value = f64::INFINITY;
+        self.max_value = f64::NEG_INFINITY;
+        for node in self.nodes.iter().filter(|node| node.visits > 0) {
+            let mean = node.total / node.visits as f64;
+            self.min_value = self.min_value.min(mean);
+            self.max_value = self.max_value.max(mean);
+        }
+    }
+
+    fn normalize(&self, value: f64) -> f64 {
+        if self.max_value > self.min_value {
+            (value - self.min_value) / (self.max_value - self.min_value)
+        } else {
+            0.5
+        }
+    }
+
+    fn select(&self, id: usize) -> usize {
+        let node = &self.nodes[id];
+        let log_visits = (node.visits.max(1) as f64).ln();
+
+        let mut best_child = node.children[0];
+        let mut best_ucb = f64::NEG_INFINITY;
+        for &child_id in node.children.iter() {
+            let child = &self.nodes[child_id];
+            let ucb = if child.visits == 0 {
+                f64::INFINITY
+            } else {
+                let mean = self.normalize(child.total / child.visits as f64);
+                mean + self.exploration * (log_visits / child.visits as f64).sqrt()
+            };
+            if ucb > best_ucb {
+                best_ucb = ucb;
+                best_child = child_id;
+            }
+        }
+        best_child
+    }
+
+    fn expand(&mut self, id: usize, track: &Track, budget: &mut Budget) -> usize {
+        let action = self.rng.action(&track.rules);
+        let mut pod = self.nodes[id].pod.clone_pod();
+        pod.apply_move(&action, track);
+        budget.consume(1);
+
+        let child = Node {
+            pod,
+            action,
+            children: Vec::new(),
+            visits: 0,
+            total: 0.0,
+            depth: self.nodes[id].depth + 1,
+        };
+        self.nodes.push(child);
+        let child_id = self.nodes.len() - 1;
+        self.nodes[id].children.push(child_id);
+        child_id
+    }
+
+    fn value(&mut self, id: usize, track: &Track, budget: &mut Budget) -> f64 {
+        match self.rollout {
+            Rollout::Fitness => evaluate(&self.nodes[id].pod, track),
+            Rollout::Random(turns) => {
+                let mut pod = self.nodes[id].pod.clone_pod();
+                for _ in 0..turns {
+                    if pod.done {
+                        break;
+                    }
+                    let action = self.rng.action(&track.rules);
+                    pod.apply_move(&action, track);
+                }
+                budget.consume(pod.turn - self.nodes[id].pod.turn);
+                evaluate(&pod, track)
+            }
+        }
+    }
+
+    fn iterate(&mut self, track: &Track, budget: &mut Budget) {
+        let mut path = vec![0];
+        let mut id = 0;
+        loop {
+            let node = &self.nodes[id];
+            if node.pod.done || node.depth >= self.max_depth {
+                break;
+            }
+
+            let allowed = (self.widening * ((node.visits + 1) as f64).powf(self.widening_exponent))
+                .ceil() as usize;
+            if node.children.len() < allowed {
+                id = self.expand(id, track, budget);
+                path.push(id);
+                break;
+            }
+
+            id = self.select(id);
+            path.push(id);
+        }
+
+        let value = self.value(id, track, budget);
+        self.min_value = self.min_value.min(value);
+        self.max_value = self.max_value.max(value);
+
+        for &node_id in path.iter() {
+            let node = &mut self.nodes[node_id];
+            node.visits += 1;
+            node.total += value;
+        }
+    }
+}
+
+impl Default for MonteCarloTreeSearch {
+    fn default() -> Self {
+        MonteCarloTreeSearch::new(2_000, 8, Rollout::Random(5), 0)
+    }
+}
+
+impl Planner for MonteCarloTreeSearch {
+    fn next_action(&mut self, track: &Track, pod: &Pod, budget: &mut Budget) -> Action {
+        // The tree survives from one turn to the next when the referee reports the state we
+        // expected after the last action, otherwise it is rebuilt from scratch
+        match self.played.take() {
+            Some(id) if self.nodes[id].pod.same_state(pod) => self.reroot(id),
+            _ => self.reset(pod),
+        }
+        self.nodes[0].pod = pod.clone_pod();
+
+        for _ in 0..self.iterations {
+            if budget.is_exhausted() {
+                break;
+            }
+            self.iterate(track, budget);
+        }
+
+        // Robust child: the most visited action of the root
+        let root = &self.nodes[0];
+        match root
+            .children
+            .iter()
+            .max_by_key(|&&id| self.nodes[id].visits)
+        {
+            Some(&id) => {
+                self.played = Some(id);
+                self.nodes[id].action
+            }
+            None => Action::new(200, 0),
+        }
+    }
+}
+
+impl Search for MonteCarloTreeSearch {
+    fn search(&mut self, track: &Track, pod: &Pod, budget: &mut Budget) -> SearchResult {
+        play(self, track, pod, budget)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::race::Race;
+
+    #[test]
+    fn test_progressive_widening() {
+        let race = Race::load("testcases/test1.json").unwrap();
+        let pod = race.initial_pod();
+        let mut mcts = MonteCarloTreeSearch::new(100, 5, Rollout::Fitness, 0);
+
+        mcts.next_action(&race.track, &pod, &mut Budget::unlimited());
+
+        // Far less children than the 7437 available actions
+        let root = &mcts.nodes[0];
+        assert_eq!(root.visits, 100);
+        assert!(root.children.len() <= (2.0 * 101f64.sqrt()).ceil() as usize);
+    }
+
+    #[test]
+    fn test_tree_reuse() {
+        let race = Race::load("testcases/test1.json").unwrap();
+        let pod = race.initial_pod();
+        let mut mcts = MonteCarloTreeSearch::new(200, 5, Rollout::Fitness, 0);
+
+        let action = mcts.next_action(&race.track, &pod, &mut Budget::unlimited());
+        let mut next = pod.clone_pod();
+        next.apply_move(&action, &race.track);
+        let kept = mcts.nodes[mcts.played.unwrap()].visits;
+
+        // Expected state: the subtree of the played action becomes the new tree
+        mcts.next_action(&race.track, &next, &mut Budget::unlimited());
+        assert_eq!(mcts.nodes[0].visits, kept + 200);
+        assert_eq!(mcts.nodes[0].depth, 0);
+        for node in mcts.nodes.iter() {
+            for &child in node.children.iter() {
+                assert_eq!(mcts.nodes[child].depth, node.depth + 1);
+            }
+        }
+
+        // Unexpected state: everything is discarded
+        let mut other = next.clone_pod();
+        other.x += 1.0;
+        mcts.next_action(&race.track, &other, &mut Budget::unlimited());
+        assert_eq!(mcts.nodes[0].visits, 200);
+    }
+
+    #[test]
+    fn test_mcts_finishes_race() {
+        let race = Race::load("testcases/test1.json").unwrap();
+        let pod = race.initial_pod();
+        let mut search = MonteCarloTreeSearch::new(100, 5, Rollout::Random(3), 1);
+
+        let result = search.search(&race.track, &pod, &mut Budget::unlimited());
+
+        assert!(result.finished);
+        assert_eq!(race.score(&result.actions), Some(result.score));
+    }
+}
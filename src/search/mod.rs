@@ -0,0 +1,231 @@
+pub mod annealing;
+pub mod beam;
+pub mod cem;
+pub mod cmaes;
+pub mod genetic;
+pub mod greedy;
+pub mod mcts;
+pub mod nrpa;
+pub mod registry;
+pub mod replay;
+pub mod rhea;
+pub mod rng;
+
+pub use crate::search::annealing::SimulatedAnnealing;
+pub use crate::search::beam::BeamSearch;
+pub use crate::search::cem::{CemMode, CrossEntropy};
+pub use crate::search::cmaes::CmaEs;
+pub use crate::search::genetic::GeneticSearch;
+pub use crate::search::greedy::GreedySearch;
+pub use crate::search::mcts::{MonteCarloTreeSearch, Rollout};
+pub use crate::search::nrpa::NestedRolloutPolicy;
+pub use crate::search::registry::{PLANNERS, SOLVERS, by_name, by_name_from, planner_by_name};
+pub use crate::search::replay::Replay;
+pub use crate::search::rhea::RollingHorizon;
+
+use std::time::{Duration, Instant};
+
+use crate::game::action::Action;
+use crate::game::pod::Pod;
+use crate::game::rules::GameRules;
+use crate::game::track::Track;
+
+// CodinGame time limits of the Search Race
+pub const FIRST_TURN_TIME: Duration = Duration::from_millis(1000);
+pub const TURN_TIME: Duration = Duration::from_millis(50);
+// Kept out of the time limits for reading the input and printing the command
+pub const SAFETY_MARGIN: Duration = Duration::from_millis(5);
+
+#[derive(Debug, Clone)]
+pub struct Budget {
+    pub max_evaluations: Option<usize>,
+    pub evaluations: usize,
+    pub deadline: Option<Instant>,
+}
+
+impl Budget {
+    pub fn unlimited() -> Self {
+        Budget {
+            max_evaluations: None,
+            evaluations: 0,
+            deadline: None,
+        }
+    }
+
+    pub fn evaluations(max_evaluations: usize) -> Self {
+        Budget {
+            max_evaluations: Some(max_evaluations),
+            ..Budget::unlimited()
+        }
+    }
+
+    pub fn time(limit: Duration) -> Self {
+        Budget::unlimited().with_time(limit)
+    }
+
+    pub fn first_turn() -> Self {
+        Budget::time(FIRST_TURN_TIME - SAFETY_MARGIN)
+    }
+
+    pub fn turn() -> Self {
+        Budget::time(TURN_TIME - SAFETY_MARGIN)
+    }
+
+    pub fn with_time(mut self, limit: Duration) -> Self {
+        // The clock starts when the budget is created
+        self.deadline = Some(Instant::now() + limit);
+        self
+    }
+
+    pub fn consume(&mut self, evaluations: usize) {
+        self.evaluations += evaluations;
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        if self
+            .max_evaluations
+            .is_some_and(|max| self.evaluations >= max)
+        {
+            return true;
+        }
+        match self.deadline {
+            Some(deadline) => Instant::now() >= deadline,
+            None => false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SearchResult {
+    pub actions: Vec<Action>,
+    pub score: f64,
+    pub finished: bool,
+}
+
+impl SearchResult {
+    pub fn from_actions(track: &Track, pod: &Pod, actions: Vec<Action>) -> Self {
+        let mut pod = pod.clone_pod();
+        pod.apply_moves(&actions, track);
+
+        SearchResult {
+            actions,
+            score: pod.last_score,
+            finished: pod.is_finished(track),
+        }
+    }
+
+    pub fn action_string(&self) -> String {
+        self.actions
+            .iter()
+            .map(|action| action.to_string())
+            .collect::<Vec<String>>()
+            .join(";")
+    }
+}
+
+pub trait Search {
+    fn search(&mut self, track: &Track, pod: &Pod, budget: &mut Budget) -> SearchResult;
+}
+
+// Turn-by-turn counterpart of Search, for the live bot: only the next action is needed
+pub trait Planner {
+    fn next_action(&mut self, track: &Track, pod: &Pod, budget: &mut Budget) -> Action;
+}
+
+impl<P: Planner + ?Sized> Planner for Box<P> {
+    fn next_action(&mut self, track: &Track, pod: &Pod, budget: &mut Budget) -> Action {
+        (**self).next_action(track, pod, budget)
+    }
+}
+
+pub fn play<P: Planner + ?Sized>(
+    planner: &mut P,
+    track: &Track,
+    pod: &Pod,
+    budget: &mut Budget,
+) -> SearchResult {
+    let mut base_pod = pod.clone_pod();
+    let mut actions: Vec<Action> = Vec::new();
+    while !base_pod.done && !budget.is_exhausted() {
+        let action = planner.next_action(track, &base_pod, budget);
+        base_pod.apply_move(&action, track);
+        actions.push(action);
+    }
+
+    SearchResult::from_actions(track, pod, actions)
+}
+
+pub fn evaluate(pod: &Pod, track: &Track) -> f64 {
+    // Higher is better. A finished race always beats an unfinished one, and among
+    // finished races the earliest crossing of the finish line wins.
+    if pod.is_finished(track) {
+        1_000_000_000.0 - pod.last_score
+    } else {
+        pod.fitness(track)
+    }
+}
+
+// Actions tried by the solvers that enumerate them, resolved against the rules of the track
+#[derive(Debug, Clone, PartialEq)]
+pub enum Candidates {
+    // Every action the rules allow
+    All,
+    // Evenly spaced thrusts x angles, the bounds of the rules included
+    Grid(usize, usize),
+    // A given list, played as is
+    Fixed(Vec<Action>),
+}
+
+impl Candidates {
+    pub fn actions(&self, rules: &GameRules) -> Vec<Action> {
+        match self {
+            Candidates::All => rules.all_actions(),
+            Candidates::Grid(thrusts, angles) => rules.action_grid(*thrusts, *angles),
+            Candidates::Fixed(actions) => actions.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_evaluations() {
+        let mut budget = Budget::evaluations(10);
+        assert!(!budget.is_exhausted());
+        budget.consume(10);
+        assert!(budget.is_exhausted());
+        assert!(!Budget::unlimited().is_exhausted());
+    }
+
+    #[test]
+    fn test_budget_deadline() {
+        let budget = Budget::time(Duration::from_millis(20));
+        assert!(!budget.is_exhausted());
+        std::thread::sleep(Duration::from_millis(25));
+        assert!(budget.is_exhausted());
+
+        // Whichever limit comes first stops the search
+        let mut budget = Budget::evaluations(5).with_time(Duration::from_secs(60));
+        budget.consume(5);
+        assert!(budget.is_exhausted());
+    }
+
+    #[test]
+    fn test_candidates_follow_rules() {
+        let rules = GameRules {
+            max_rotation: 30,
+            ..GameRules::coders_strike_back()
+        };
+
+        let grid = Candidates::Grid(3, 3).actions(&rules);
+        assert_eq!(grid.first(), Some(&Action::new(0, -30)));
+        assert_eq!(grid.last(), Some(&Action::new(100, 30)));
+        assert_eq!(Candidates::All.actions(&rules).len(), 101 * 61);
+
+        // A given list is kept as is, even out of the bounds of the rules
+        let fixed = vec![Action::new(200, 0)];
+        assert_eq!(Candidates::Fixed(fixed.clone()).actions(&rules), fixed);
+    }
+}
@@ -0,0 +1,260 @@
+use crate::game::action::Action;
+use crate::game::pod::Pod;
+use crate::game::point::Point;
+use crate::game::rules::GameRules;
+use crate::game::track::Track;
+use crate::search::rng::Rng;
+use crate::search::{Budget, Candidates, Search, SearchResult, evaluate};
+
+#[derive(Debug)]
+pub struct NestedRolloutPolicy {
+    pub level: usize,
+    pub iterations: usize,
+    pub alpha: f64,
+    // Discretized action codes the policy chooses from
+    pub candidates: Candidates,
+    // Number of buckets used to discretize the angle between the pod and its next checkpoint
+    pub angle_buckets: usize,
+    // Distance thresholds splitting each angle bucket (e.g. far / approaching / close)
+    pub distance_thresholds: Vec<f64>,
+    // Strength of the fixed prior steering the pod towards its next checkpoint (0 = uniform policy)
+    pub bias: f64,
+    pub seed: u64,
+}
+
+// (feature bucket, action code) chosen at each turn of a rollout
+type Sequence = Vec<(usize, usize)>;
+
+impl NestedRolloutPolicy {
+    pub fn new(level: usize, iterations: usize, seed: u64) -> Self {
+        NestedRolloutPolicy {
+            level,
+            iterations,
+            alpha: 1.0,
+            candidates: Candidates::Grid(5, 5),
+            angle_buckets: 18,
+            distance_thresholds: vec![2_000.0, 5_000.0],
+            bias: 3.0,
+            seed,
+        }
+    }
+
+    fn buckets(&self) -> usize {
+        // A single bucket when the angle is not used
+        self.angle_buckets.max(1)
+    }
+
+    fn n_features(&self) -> usize {
+        self.buckets() * (self.distance_thresholds.len() + 1)
+    }
+
+    fn feature(&self, pod: &Pod, track: &Track) -> usize {
+        let checkpoint = track.next(pod.next_checkpoint_id);
+        let checkpoint_point = Point::from_f64(checkpoint.x, checkpoint.y);
+
+        // diff is in [-180, 180]
+        let diff = pod.diff_angle(&checkpoint_point);
+        let angle_bucket = ((diff + 180.0) / 360.0 * self.buckets() as f64) as usize;
+        let angle_bucket = angle_bucket.min(self.buckets() - 1);
+
+        let dist = pod.distance(&checkpoint_point);
+        let distance_bucket = self
+            .distance_thresholds
+            .iter()
+            .filter(|&&threshold| dist > threshold)
+            .count();
+
+        angle_bucket * (self.distance_thresholds.len() + 1) + distance_bucket
+    }
+
+    fn prior(&self, actions: &[Action], rules: &GameRules) -> Vec<f64> {
+        // Generalized NRPA: the bias is added to the learned weights but never adapted.
+        // Codes turning towards the centre of the angle bucket at full thrust are preferred.
+        let n_distances = self.distance_thresholds.len() + 1;
+        let rotation = rules.max_rotation as f64;
+        let mut prior = Vec::with_capacity(self.n_features() * actions.len());
+        for feature in 0..self.n_features() {
+            let bucket = feature / n_distances;
+            let centre = (bucket as f64 + 0.5) / self.buckets() as f64 * 360.0 - 180.0;
+            let turn = centre.clamp(-rotation, rotation);
+            for action in actions.iter() {
+                let steering = (turn - action.angle as f64).abs() / rotation;
+                let braking = 1.0 - action.thrust as f64 / rules.max_thrust as f64;
+                prior.push(-self.bias * (steering + braking));
+            }
+        }
+        prior
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn rollout(
+        &self,
+        actions: &[Action],
+        policy: &[f64],
+        prior: &[f64],
+        track: &Track,
+        pod: &Pod,
+        rng: &mut Rng,
+        budget: &mut Budget,
+    ) -> (f64, Sequence) {
+        let n_codes = actions.len();
+        let mut sequence: Sequence = Vec::new();
+        let mut current = pod.clone_pod();
+
+        while !current.done {
+            let bucket = self.feature(&current, track);
+            let range = bucket * n_codes..(bucket + 1) * n_codes;
+
+            // Sampling from the softmax of the weights of this bucket
+            let exps: Vec<f64> = policy[range.clone()]
+                .iter()
+                .zip(prior[range].iter())
+                .map(|(w, b)| (w + b).exp())
+                .collect();
+            let mut target = rng.next_f64() * exps.iter().sum::<f64>();
+            let mut code = n_codes - 1;
+            for (i, e) in exps.iter().enumerate() {
+                if target < *e {
+                    code = i;
+                    break;
+                }
+                target -= e;
+            }
+
+            current.apply_move(&actions[code], track);
+            sequence.push((bucket, code));
+        }
+        budget.consume(current.turn - pod.turn);
+
+        (evaluate(&current, track), sequence)
+    }
+
+    fn adapt(&self, policy: &[f64], prior: &[f64], sequence: &Sequence) -> Vec<f64> {
+        let n_codes = policy.len() / self.n_features();
+        let mut adapted = policy.to_vec();
+
+        for &(bucket, code) in sequence.iter() {
+            let range = bucket * n_codes..(bucket + 1) * n_codes;
+            let exps: Vec<f64> = policy[range.clone()]
+                .iter()
+                .zip(prior[range].iter())
+                .map(|(w, b)| (w + b).exp())
+                .collect();
+            let z: f64 = exps.iter().sum();
+
+            adapted[bucket * n_codes + code] += self.alpha;
+            for (k, e) in exps.iter().enumerate() {
+                adapted[bucket * n_codes + k] -= self.alpha * e / z;
+            }
+        }
+        adapted
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn nrpa(
+        &self,
+        level: usize,
+        actions: &[Action],
+        mut policy: Vec<f64>,
+        prior: &[f64],
+        track: &Track,
+        pod: &Pod,
+        rng: &mut Rng,
+        budget: &mut Budget,
+    ) -> (f64, Sequence) {
+        if level == 0 {
+            return self.rollout(actions, &policy, prior, track, pod, rng, budget);
+        }
+
+        let mut best: (f64, Sequence) = (f64::NEG_INFINITY, Vec::new());
+        for _ in 0..self.iterations {
+            let result = self.nrpa(
+                level - 1,
+                actions,
+                policy.clone(),
+                prior,
+                track,
+                pod,
+                rng,
+                budget,
+            );
+            if result.0 >= best.0 {
+                best = result;
+            }
+            policy = self.adapt(&policy, prior, &best.1);
+
+            if budget.is_exhausted() {
+                break;
+            }
+        }
+        best
+    }
+}
+
+impl Default for NestedRolloutPolicy {
+    fn default() -> Self {
+        NestedRolloutPolicy::new(2, 50, 0)
+    }
+}
+
+impl Search for NestedRolloutPolicy {
+    fn search(&mut self, track: &Track, pod: &Pod, budget: &mut Budget) -> SearchResult {
+        let mut rng = Rng::new(self.seed);
+        let actions = self.candidates.actions(&track.rules);
+        let policy = vec![0.0; self.n_features() * actions.len()];
+        let prior = self.prior(&actions, &track.rules);
+
+        let (_, sequence) = self.nrpa(
+            self.level, &actions, policy, &prior, track, pod, &mut rng, budget,
+        );
+
+        let played = sequence.iter().map(|&(_, code)| actions[code]).collect();
+        SearchResult::from_actions(track, pod, played)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::race::Race;
+
+    #[test]
+    fn test_adapt_favours_sequence() {
+        let search = NestedRolloutPolicy::new(1, 1, 0);
+        let n_codes = search.candidates.actions(&GameRules::default()).len();
+        let policy = vec![0.0; search.n_features() * n_codes];
+        let prior = vec![0.0; search.n_features() * n_codes];
+
+        let adapted = search.adapt(&policy, &prior, &vec![(3, 7)]);
+
+        assert!(adapted[3 * n_codes + 7] > 0.0);
+        assert!(adapted[3 * n_codes] < 0.0);
+        // Other buckets are untouched
+        assert_eq!(adapted[4 * n_codes + 7], 0.0);
+    }
+
+    #[test]
+    fn test_nrpa_result_is_replayable() {
+        let race = Race::load("testcases/test1.json").unwrap();
+        let pod = race.initial_pod();
+        let mut search = NestedRolloutPolicy::new(1, 5, 2);
+
+        let result = search.search(&race.track, &pod, &mut Budget::unlimited());
+
+        let replayed = race.simulate(&result.actions);
+        assert_eq!(replayed.last_score, result.score);
+        assert_eq!(race.is_finished(&replayed), result.finished);
+    }
+    #[test]
+    fn test_without_angle_buckets() {
+        let race = Race::load("testcases/test1.json").unwrap();
+        let pod = race.initial_pod();
+        let mut search = NestedRolloutPolicy {
+            angle_buckets: 0,
+            ..NestedRolloutPolicy::new(1, 2, 0)
+        };
+
+        let result = search.search(&race.track, &pod, &mut Budget::unlimited());
+        assert_eq!(race.simulate(&result.actions).last_score, result.score);
+    }
+}
@@ -0,0 +1,112 @@
+use crate::game::action::Action;
+use crate::search::{
+    BeamSearch, CemMode, CmaEs, CrossEntropy, GeneticSearch, GreedySearch, MonteCarloTreeSearch,
+    NestedRolloutPolicy, Planner, RollingHorizon, Rollout, Search, SimulatedAnnealing,
+};
+
+// Solvers by their command line name. Kept apart from the core of the module so that the
+// submission only embeds the solvers it plays with.
+pub const SOLVERS: [&str; 9] = [
+    "greedy",
+    "beam",
+    "genetic",
+    "rhea",
+    "annealing",
+    "mcts",
+    "nrpa",
+    "cmaes",
+    "cem",
+];
+
+pub fn by_name(name: &str, seed: u64) -> Option<Box<dyn Search>> {
+    by_name_from(name, seed, Vec::new())
+}
+
+// Same, the solvers refining a solution start from `initial` (e.g. the stored best) when given
+pub fn by_name_from(name: &str, seed: u64, initial: Vec<Action>) -> Option<Box<dyn Search>> {
+    let search: Box<dyn Search> = match name {
+        "greedy" => Box::new(GreedySearch::default()),
+        "beam" => Box::new(BeamSearch::default()),
+        "genetic" => Box::new(GeneticSearch {
+            seed,
+            ..GeneticSearch::default()
+        }),
+        "rhea" => Box::new(RollingHorizon::new(20, 8, 40, seed)),
+        "annealing" => Box::new(SimulatedAnnealing {
+            initial,
+            seed,
+            ..SimulatedAnnealing::default()
+        }),
+        "mcts" => Box::new(MonteCarloTreeSearch::new(
+            2_000,
+            8,
+            Rollout::Random(5),
+            seed,
+        )),
+        "nrpa" => Box::new(NestedRolloutPolicy {
+            seed,
+            ..NestedRolloutPolicy::default()
+        }),
+        "cmaes" => Box::new(CmaEs {
+            seed,
+            ..CmaEs::default()
+        }),
+        "cem" => Box::new(CrossEntropy::new(CemMode::Online, 15, 50, 10, seed)),
+        _ => return None,
+    };
+    Some(search)
+}
+
+pub const PLANNERS: [&str; 4] = ["greedy", "rhea", "mcts", "cem"];
+
+pub fn planner_by_name(name: &str, seed: u64) -> Option<Box<dyn Planner>> {
+    // Iteration counts are left high on purpose: the time budget of the turn stops the search
+    let planner: Box<dyn Planner> = match name {
+        "greedy" => Box::new(GreedySearch::default()),
+        "rhea" => Box::new(RollingHorizon::new(20, 8, 100_000, seed)),
+        "mcts" => Box::new(MonteCarloTreeSearch::new(
+            1_000_000,
+            8,
+            Rollout::Random(5),
+            seed,
+        )),
+        "cem" => Box::new(CrossEntropy::new(CemMode::Online, 15, 50, 1_000, seed)),
+        _ => return None,
+    };
+    Some(planner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::race::Race;
+    use crate::search::Budget;
+
+    #[test]
+    fn test_by_name() {
+        for name in SOLVERS {
+            assert!(by_name(name, 0).is_some(), "{}", name);
+        }
+        assert!(by_name("unknown", 0).is_none());
+
+        for name in PLANNERS {
+            assert!(planner_by_name(name, 0).is_some(), "{}", name);
+        }
+        assert!(planner_by_name("beam", 0).is_none());
+    }
+
+    #[test]
+    fn test_by_name_from() {
+        let race = Race::load("testcases/test1.json").unwrap();
+        let pod = race.initial_pod();
+        let initial = by_name("beam", 0)
+            .unwrap()
+            .search(&race.track, &pod, &mut Budget::unlimited())
+            .actions;
+
+        // Without any budget the annealing gives back where it started from
+        let mut search = by_name_from("annealing", 0, initial.clone()).unwrap();
+        let result = search.search(&race.track, &pod, &mut Budget::evaluations(0));
+        assert_eq!(result.actions, initial);
+    }
+}
@@ -0,0 +1,85 @@
+use crate::game::action::Action;
+use crate::game::pod::Pod;
+use crate::game::track::Track;
+use crate::search::{Budget, Planner};
+
+// Plays a precomputed solution, and hands over to the fallback planner as soon as the race
+// leaves the planned trajectory or the solution runs out
+#[derive(Debug)]
+pub struct Replay<P: Planner> {
+    pub actions: Vec<Action>,
+    pub fallback: P,
+    expected: Option<Pod>,
+    replaying: bool,
+}
+
+impl<P: Planner> Replay<P> {
+    pub fn new(actions: Vec<Action>, fallback: P) -> Self {
+        Replay {
+            actions,
+            fallback,
+            expected: None,
+            replaying: true,
+        }
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        self.replaying
+    }
+}
+
+impl<P: Planner> Planner for Replay<P> {
+    fn next_action(&mut self, track: &Track, pod: &Pod, budget: &mut Budget) -> Action {
+        if self
+            .expected
+            .take()
+            .is_some_and(|expected| !expected.same_state(pod))
+        {
+            self.replaying = false;
+        }
+
+        if let Some(action) = self
+            .actions
+            .get(pod.turn)
+            .copied()
+            .filter(|_| self.replaying)
+        {
+            let mut expected = pod.clone_pod();
+            expected.apply_move(&action, track);
+            self.expected = Some(expected);
+            return action;
+        }
+
+        self.replaying = false;
+        self.fallback.next_action(track, pod, budget)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::race::Race;
+    use crate::search::GreedySearch;
+
+    #[test]
+    fn test_replay_falls_back_on_unexpected_state() {
+        let race = Race::load("testcases/test1.json").unwrap();
+        let actions = vec![Action::new(0, 18); 3];
+        let mut replay = Replay::new(actions, GreedySearch::default());
+        let mut budget = Budget::unlimited();
+
+        let mut pod = race.initial_pod();
+        let action = replay.next_action(&race.track, &pod, &mut budget);
+        assert_eq!(action, Action::new(0, 18));
+        race.step(&mut pod, &action);
+
+        let action = replay.next_action(&race.track, &pod, &mut budget);
+        assert_eq!(action, Action::new(0, 18));
+        assert!(replay.is_replaying());
+
+        // Not the state the replayed action leads to
+        race.step(&mut pod, &Action::new(200, 0));
+        replay.next_action(&race.track, &pod, &mut budget);
+        assert!(!replay.is_replaying());
+    }
+}
@@ -0,0 +1,218 @@
+use crate::game::action::Action;
+use crate::game::pod::Pod;
+use crate::game::rules::GameRules;
+use crate::game::track::Track;
+use crate::search::rng::Rng;
+use crate::search::{Budget, Planner, Search, SearchResult, evaluate, play};
+
+#[derive(Debug)]
+pub struct RollingHorizon {
+    pub horizon: usize,
+    pub population: usize,
+    pub generations: usize,
+    pub elites: usize,
+    pub thrust_mutation: f64,
+    pub angle_mutation: f64,
+    rng: Rng,
+    genomes: Vec<Vec<Action>>,
+    // State the population was shifted for, the warm start is only valid from there
+    expected: Option<Pod>,
+}
+
+impl RollingHorizon {
+    pub fn new(horizon: usize, population: usize, generations: usize, seed: u64) -> Self {
+        RollingHorizon {
+            horizon,
+            population,
+            generations,
+            elites: 1,
+            thrust_mutation: 0.3,
+            angle_mutation: 0.3,
+            rng: Rng::new(seed),
+            genomes: Vec::new(),
+            expected: None,
+        }
+    }
+
+    fn mutate(&mut self, genome: &mut [Action], rules: &GameRules) {
+        let rotation = rules.max_rotation;
+        for gene in genome.iter_mut() {
+            if self.rng.chance(self.thrust_mutation) {
+                gene.thrust = (gene.thrust + self.rng.range(-100, 100)).clamp(0, rules.max_thrust);
+            }
+            if self.rng.chance(self.angle_mutation) {
+                gene.angle =
+                    (gene.angle + self.rng.range(-rotation, rotation)).clamp(-rotation, rotation);
+            }
+        }
+    }
+
+    fn score(genome: &[Action], track: &Track, pod: &Pod, budget: &mut Budget) -> f64 {
+        let mut child = pod.clone_pod();
+        child.apply_moves(genome, track);
+        budget.consume(child.turn - pod.turn);
+        evaluate(&child, track)
+    }
+
+    fn shift(&mut self, rules: &GameRules) {
+        // Warm start: what was planned for the next turns becomes the new plan
+        for i in 0..self.genomes.len() {
+            self.genomes[i].remove(0);
+            let action = self.rng.action(rules);
+            self.genomes[i].push(action);
+        }
+    }
+}
+
+impl Default for RollingHorizon {
+    fn default() -> Self {
+        RollingHorizon::new(20, 8, 40, 0)
+    }
+}
+
+impl Planner for RollingHorizon {
+    fn next_action(&mut self, track: &Track, pod: &Pod, budget: &mut Budget) -> Action {
+        if self
+            .expected
+            .take()
+            .is_some_and(|expected| !expected.same_state(pod))
+        {
+            self.genomes.clear();
+        }
+
+        // The best plan of the population gives the action, there has to be at least one turn of it
+        let (horizon, size) = (self.horizon.max(1), self.population.max(1));
+        while self.genomes.len() < size {
+            let genome = (0..horizon)
+                .map(|_| self.rng.action(&track.rules))
+                .collect();
+            self.genomes.push(genome);
+        }
+
+        let mut population: Vec<(f64, Vec<Action>)> = std::mem::take(&mut self.genomes)
+            .into_iter()
+            .map(|genome| (RollingHorizon::score(&genome, track, pod, budget), genome))
+            .collect();
+
+        for _ in 0..self.generations {
+            if budget.is_exhausted() {
+                break;
+            }
+
+            population.sort_by(|a, b| b.0.total_cmp(&a.0));
+            population.truncate(self.elites.max(1));
+
+            // Every offspring is a mutated copy of one of the survivors of the previous generation
+            while population.len() < size {
+                let parent = self.rng.below(self.elites.max(1).min(population.len()));
+                let mut genome = population[parent].1.clone();
+                self.mutate(&mut genome, &track.rules);
+                let score = RollingHorizon::score(&genome, track, pod, budget);
+                population.push((score, genome));
+            }
+        }
+
+        population.sort_by(|a, b| b.0.total_cmp(&a.0));
+        let best = population[0].1[0];
+
+        self.genomes = population.into_iter().map(|(_, genome)| genome).collect();
+        self.shift(&track.rules);
+
+        let mut expected = pod.clone_pod();
+        expected.apply_move(&best, track);
+        self.expected = Some(expected);
+
+        best
+    }
+}
+
+impl Search for RollingHorizon {
+    fn search(&mut self, track: &Track, pod: &Pod, budget: &mut Budget) -> SearchResult {
+        play(self, track, pod, budget)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::race::Race;
+
+    #[test]
+    fn test_shift_keeps_plan() {
+        let race = Race::load("testcases/test1.json").unwrap();
+        let pod = race.initial_pod();
+        let mut planner = RollingHorizon::new(5, 4, 3, 0);
+
+        planner.next_action(&race.track, &pod, &mut Budget::unlimited());
+        assert_eq!(planner.genomes.len(), 4);
+        assert!(planner.genomes.iter().all(|genome| genome.len() == 5));
+
+        // Each plan moves forward by one turn, only its last action is new
+        let evolved = planner.genomes.clone();
+        planner.shift(&race.track.rules);
+        for (shifted, genome) in planner.genomes.iter().zip(evolved.iter()) {
+            assert_eq!(shifted.len(), 5);
+            assert_eq!(shifted[..4], genome[1..]);
+        }
+    }
+
+    #[test]
+    fn test_empty_horizon() {
+        let race = Race::load("testcases/test1.json").unwrap();
+        let pod = race.initial_pod();
+        let mut planner = RollingHorizon::new(0, 0, 3, 0);
+
+        let action = planner.next_action(&race.track, &pod, &mut Budget::unlimited());
+        assert!(race.track.rules.allows(&action));
+    }
+
+    fn plans(genomes: &[Vec<Action>], turns: std::ops::Range<usize>) -> Vec<Vec<(i32, i32)>> {
+        let mut plans: Vec<Vec<(i32, i32)>> = genomes
+            .iter()
+            .map(|g| {
+                g[turns.clone()]
+                    .iter()
+                    .map(|a| (a.thrust, a.angle))
+                    .collect()
+            })
+            .collect();
+        plans.sort();
+        plans
+    }
+
+    #[test]
+    fn test_population_reset_on_unexpected_state() {
+        let race = Race::load("testcases/test1.json").unwrap();
+        let pod = race.initial_pod();
+        // Without generations the population is only evaluated and shifted
+        let mut planner = RollingHorizon::new(5, 4, 0, 0);
+
+        let action = planner.next_action(&race.track, &pod, &mut Budget::unlimited());
+        let mut next = pod.clone_pod();
+        next.apply_move(&action, &race.track);
+
+        // Expected state: the plans of the population move forward by one turn
+        let kept = plans(&planner.genomes, 1..4);
+        planner.next_action(&race.track, &next, &mut Budget::unlimited());
+        assert_eq!(plans(&planner.genomes, 0..3), kept);
+
+        // Any other state throws them away
+        let kept = plans(&planner.genomes, 1..4);
+        let mut other = next.clone_pod();
+        other.vx += 1.0;
+        planner.next_action(&race.track, &other, &mut Budget::unlimited());
+        assert_ne!(plans(&planner.genomes, 0..3), kept);
+    }
+
+    #[test]
+    fn test_rolling_horizon_finishes_race() {
+        let race = Race::load("testcases/test1.json").unwrap();
+        let pod = race.initial_pod();
+        let mut search = RollingHorizon::new(10, 6, 10, 3);
+
+        let result = search.search(&race.track, &pod, &mut Budget::unlimited());
+
+        assert!(result.finished);
+        assert_eq!(race.score(&result.actions), Some(result.score));
+    }
+}
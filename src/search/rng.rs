@@ -0,0 +1,93 @@
+use crate::game::action::Action;
+use crate::game::rules::GameRules;
+
+// Xorshift64*: good enough for the searches and keeps the crate free of dependencies
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // The state must never be zero
+        Rng {
+            state: (seed ^ 0x9E37_79B9_7F4A_7C15) | 1,
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    pub fn next_f64(&mut self) -> f64 {
+        // 53 bits of mantissa, in [0, 1)
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn range(&mut self, min: i32, max: i32) -> i32 {
+        // Both bounds are included
+        min + self.below((max - min + 1) as usize) as i32
+    }
+
+    pub fn chance(&mut self, p: f64) -> bool {
+        self.next_f64() < p
+    }
+
+    pub fn action(&mut self, rules: &GameRules) -> Action {
+        // Any action the rules allow, uniformly
+        Action::new(
+            self.range(0, rules.max_thrust),
+            self.range(-rules.max_rotation, rules.max_rotation),
+        )
+    }
+
+    pub fn gaussian(&mut self) -> f64 {
+        // Box-Muller, 1 - u keeps the logarithm away from 0
+        let u = 1.0 - self.next_f64();
+        let v = self.next_f64();
+        (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+    }
+
+    #[test]
+    fn test_range_bounds() {
+        let mut rng = Rng::new(0);
+        for _ in 0..1000 {
+            let v = rng.range(-18, 18);
+            assert!((-18..=18).contains(&v));
+            let f = rng.next_f64();
+            assert!((0.0..1.0).contains(&f));
+        }
+    }
+
+    #[test]
+    fn test_gaussian_moments() {
+        let mut rng = Rng::new(3);
+        let samples: Vec<f64> = (0..10_000).map(|_| rng.gaussian()).collect();
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let var = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+
+        assert!(mean.abs() < 0.05, "mean: {}", mean);
+        assert!((var - 1.0).abs() < 0.05, "variance: {}", var);
+    }
+}
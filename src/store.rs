@@ -0,0 +1,273 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, ParseError};
+use crate::game::action::{Action, parse_actions};
+use crate::game::codec;
+use crate::game::rules::GameRules;
+use crate::race::Race;
+use crate::verify;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredSolution {
+    pub map: String,
+    pub score: f64,
+    pub solver: String,
+    pub seed: u64,
+    pub date: String,
+    pub actions: String,
+}
+
+impl StoredSolution {
+    pub fn actions(&self) -> Result<Vec<Action>, ParseError> {
+        // Entries written before the packed codec hold the plain "thrust,angle;..." string
+        if self.actions.contains(',') {
+            parse_actions(&self.actions)
+        } else {
+            codec::decode(&self.actions)
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Submission {
+    Accepted { previous: Option<f64> },
+    Rejected { best: f64 },
+    Invalid(String),
+}
+
+#[derive(Debug)]
+pub struct Store {
+    dir: PathBuf,
+}
+
+impl Store {
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self, Error> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir).map_err(|source| Error::Io {
+            path: dir.clone(),
+            source,
+        })?;
+        Ok(Store { dir })
+    }
+
+    pub fn path(&self, map: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", map))
+    }
+
+    pub fn get(&self, map: &str) -> Result<Option<StoredSolution>, Error> {
+        let path = self.path(map);
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&path).map_err(|source| Error::Io {
+            path: path.clone(),
+            source,
+        })?;
+        let solution = serde_json::from_str(&content).map_err(|e| Error::Json {
+            path,
+            message: e.to_string(),
+        })?;
+        Ok(Some(solution))
+    }
+
+    // The actions of the best solution, none when the map was never solved
+    pub fn actions(&self, map: &str) -> Result<Vec<Action>, Error> {
+        match self.get(map)? {
+            Some(solution) => solution.actions().map_err(|source| Error::Parse {
+                path: self.path(map),
+                source,
+            }),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    pub fn entries(&self) -> Result<Vec<StoredSolution>, Error> {
+        let mut maps: Vec<String> = fs::read_dir(&self.dir)
+            .map_err(|source| Error::Io {
+                path: self.dir.clone(),
+                source,
+            })?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| Some(path.file_stem()?.to_string_lossy().to_string()))
+            .collect();
+        maps.sort();
+
+        let mut entries = Vec::new();
+        for map in maps {
+            entries.extend(self.get(&map)?);
+        }
+        Ok(entries)
+    }
+
+    pub fn submit(
+        &self,
+        map: &str,
+        race: &Race,
+        actions: &[Action],
+        solver: &str,
+        seed: u64,
+    ) -> Result<Submission, Error> {
+        // Scores under other rules are not comparable with the stored ones
+        if race.track.rules != GameRules::default() {
+            return Ok(Submission::Invalid(
+                "only solutions under the Search Race rules are stored".to_string(),
+            ));
+        }
+
+        // The score is recomputed, never trusted from the solver
+        let report = verify::verify(race, actions);
+        if !report.finished {
+            return Ok(Submission::Invalid("race not finished".to_string()));
+        }
+        if let Some((turn, action)) = report.invalid.first() {
+            return Ok(Submission::Invalid(format!(
+                "invalid action at turn {}: {}",
+                turn, action
+            )));
+        }
+
+        let previous = self.get(map)?.map(|solution| solution.score);
+        if let Some(best) = previous
+            && best <= report.score
+        {
+            return Ok(Submission::Rejected { best });
+        }
+
+        let path = self.path(map);
+        let played = &actions[..actions.len() - report.ignored];
+        let packed = codec::encode(played).map_err(|source| Error::Parse {
+            path: path.clone(),
+            source,
+        })?;
+        let solution = StoredSolution {
+            map: map.to_string(),
+            score: report.score,
+            solver: solver.to_string(),
+            seed,
+            date: today(),
+            actions: packed,
+        };
+
+        let content = serde_json::to_string_pretty(&solution).map_err(|e| Error::Json {
+            path: path.clone(),
+            message: e.to_string(),
+        })?;
+
+        // Write then rename so an interrupted run never leaves a truncated entry
+        let tmp = self.dir.join(format!(".{}.json.tmp", map));
+        fs::write(&tmp, content)
+            .and_then(|_| fs::rename(&tmp, &path))
+            .map_err(|source| Error::Io { path, source })?;
+
+        Ok(Submission::Accepted { previous })
+    }
+}
+
+fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() / 86_400)
+        .unwrap_or(0) as i64;
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::{Budget, GreedySearch, Search};
+
+    #[test]
+    fn test_submit_only_improvements() {
+        let dir = std::env::temp_dir().join(format!("search_race_store_{}", std::process::id()));
+        let store = Store::open(&dir).unwrap();
+        let race = Race::load("testcases/test1.json").unwrap();
+
+        let actions = GreedySearch::default()
+            .search(&race.track, &race.initial_pod(), &mut Budget::unlimited())
+            .actions;
+        let score = race.score(&actions).unwrap();
+
+        assert_eq!(
+            store.submit("test1", &race, &actions, "greedy", 0).unwrap(),
+            Submission::Accepted { previous: None }
+        );
+        assert_eq!(
+            store.submit("test1", &race, &actions, "greedy", 1).unwrap(),
+            Submission::Rejected { best: score }
+        );
+        assert!(matches!(
+            store.submit("test1", &race, &actions[..10], "greedy", 2),
+            Ok(Submission::Invalid(_))
+        ));
+        // One lap is quicker but must never replace the best full race
+        let one_lap = Race::load_with_rules("testcases/test1.json", GameRules::one_lap()).unwrap();
+        assert!(matches!(
+            store.submit("test1", &one_lap, &actions, "greedy", 3),
+            Ok(Submission::Invalid(_))
+        ));
+
+        let stored = store.get("test1").unwrap().unwrap();
+        assert_eq!(stored.score, score);
+        assert_eq!(stored.seed, 0);
+        assert_eq!(stored.actions().unwrap(), actions);
+        assert_eq!(store.entries().unwrap(), vec![stored]);
+        assert_eq!(store.get("test2").unwrap(), None);
+        assert_eq!(store.actions("test1").unwrap(), actions);
+        assert!(store.actions("test2").unwrap().is_empty());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_actions_plain_and_packed() {
+        let actions = vec![
+            Action::new(200, 0),
+            Action::new(150, -18),
+            Action::new(0, 18),
+        ];
+        let mut solution = StoredSolution {
+            map: "test1".to_string(),
+            score: 0.0,
+            solver: "greedy".to_string(),
+            seed: 0,
+            date: today(),
+            actions: codec::encode(&actions).unwrap(),
+        };
+        assert_eq!(solution.actions().unwrap(), actions);
+
+        solution.actions = "200,0;150,-18;0,18".to_string();
+        assert_eq!(solution.actions().unwrap(), actions);
+
+        solution.actions = "200,0;150,-1B;0,18".to_string();
+        assert_eq!(
+            solution.actions(),
+            Err(ParseError::new("angle", "-1B").at(1))
+        );
+    }
+
+    #[test]
+    fn test_today_format() {
+        let date = today();
+        assert_eq!(date.len(), 10);
+        assert!(date.as_str() >= "2024-01-01");
+        assert_eq!(&date[4..5], "-");
+    }
+}
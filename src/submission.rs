@@ -0,0 +1,282 @@
+use std::path::Path;
+
+use crate::bot;
+use crate::error::Error;
+use crate::game::action::Action;
+use crate::game::codec;
+use crate::race;
+use crate::store::Store;
+
+// CodinGame rejects any source file over this many characters
+pub const SOURCE_LIMIT: usize = 100_000;
+
+// Modules compiled into the submission: what MAIN plays with. The others depend on serde or the
+// file system, or are solvers the bot does not use, and are left out with their declarations.
+// CodinGame builds with edition 2021: no let chains in these files.
+const SOURCES: &[(&str, &str)] = &[
+    ("bot.rs", include_str!("bot.rs")),
+    ("error.rs", include_str!("error.rs")),
+    ("game/mod.rs", include_str!("game/mod.rs")),
+    ("game/action.rs", include_str!("game/action.rs")),
+    ("game/checkpoint.rs", include_str!("game/checkpoint.rs")),
+    ("game/codec.rs", include_str!("game/codec.rs")),
+    ("game/command.rs", include_str!("game/command.rs")),
+    ("game/pod.rs", include_str!("game/pod.rs")),
+    ("game/rules.rs", include_str!("game/rules.rs")),
+    ("game/point.rs", include_str!("game/point.rs")),
+    ("game/track.rs", include_str!("game/track.rs")),
+    ("search/mod.rs", include_str!("search/mod.rs")),
+    ("search/cem.rs", include_str!("search/cem.rs")),
+    ("search/replay.rs", include_str!("search/replay.rs")),
+    ("search/rng.rs", include_str!("search/rng.rs")),
+];
+
+// Inner attributes have to open the file, before the solution table
+const ATTRIBUTES: &str = "#![allow(dead_code, unused_imports)]\n";
+
+const MAIN: &str = "
+use game::GameRules;
+use search::{CemMode, CrossEntropy, Replay};
+
+fn main() {
+    let stdin = std::io::stdin();
+    let mut input = stdin.lock();
+    let map = match bot::read_checkpoints(&mut input) {
+        Ok(map) => map,
+        Err(error) => return eprintln!(\"{}\", error),
+    };
+
+    let fingerprint = bot::fingerprint(&map);
+    let actions = SOLUTIONS
+        .iter()
+        .find(|(known, _)| *known == fingerprint)
+        .and_then(|(_, actions)| game::codec::decode(actions).ok())
+        .unwrap_or_default();
+    eprintln!(\"Map {:016x}: {} stored actions\", fingerprint, actions.len());
+
+    let fallback = CrossEntropy::new(CemMode::Online, 15, 50, 1_000, 0);
+    let planner = Replay::new(actions, fallback);
+    bot::play(&mut input, &mut std::io::stdout(), &map, planner, GameRules::default()).unwrap();
+}
+";
+
+#[derive(Debug)]
+pub struct Submission {
+    pub source: String,
+    pub embedded: Vec<u64>,
+    // Solutions left out to stay under the size limit
+    pub skipped: Vec<u64>,
+}
+
+fn find(path: &str) -> Option<&'static str> {
+    SOURCES
+        .iter()
+        .find(|(name, _)| *name == path)
+        .map(|(_, content)| *content)
+}
+
+fn source(path: &str) -> &'static str {
+    find(path).unwrap_or_else(|| panic!("{} is not embedded", path))
+}
+
+fn strip(content: &str) -> String {
+    // Tests always close the file, indentation and comments are only weight here
+    let code = match content.find("#[cfg(test)]") {
+        Some(index) => &content[..index],
+        None => content,
+    };
+
+    code.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("//"))
+        .collect::<Vec<&str>>()
+        .join("\n")
+}
+
+fn inline(path: &str) -> String {
+    let dir = path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+    let embedded = |module: &str| find(&format!("{}/{}.rs", dir, module)).is_some();
+
+    strip(source(path))
+        .lines()
+        .filter_map(|line| {
+            if let Some(module) = line
+                .strip_prefix("pub mod ")
+                .and_then(|rest| rest.strip_suffix(';'))
+            {
+                // Modules left out of SOURCES are dropped with their declaration
+                return embedded(module).then(|| {
+                    format!(
+                        "pub mod {} {{\n{}\n}}",
+                        module,
+                        inline(&format!("{}/{}.rs", dir, module))
+                    )
+                });
+            }
+
+            // And so are the re-exports from them
+            let prefix = format!("pub use crate::{}::", dir.replace('/', "::"));
+            match line
+                .strip_prefix(&prefix)
+                .and_then(|rest| rest.split_once("::"))
+            {
+                Some((module, _)) if !embedded(module) => None,
+                _ => Some(line.to_string()),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn solution_line(fingerprint: u64, actions: &[Action]) -> Result<String, String> {
+    Ok(format!(
+        "(0x{:016x}, \"{}\"),\n",
+        fingerprint,
+        codec::encode(actions).map_err(|e| e.to_string())?
+    ))
+}
+
+pub fn generate(solutions: &[(u64, Vec<Action>)]) -> Result<Submission, String> {
+    let mut code = format!("mod bot {{\n{}\n}}\n", strip(source("bot.rs")));
+    code.push_str(&format!(
+        "mod error {{\n{}\n}}\n",
+        strip(source("error.rs"))
+    ));
+    code.push_str(&format!("mod game {{\n{}\n}}\n", inline("game/mod.rs")));
+    code.push_str(&format!("mod search {{\n{}\n}}\n", inline("search/mod.rs")));
+    code.push_str(MAIN);
+
+    let header = "const SOLUTIONS: &[(u64, &str)] = &[\n";
+    let footer = "];\n";
+    let mut size = ATTRIBUTES.len() + header.len() + code.chars().count() + footer.len();
+    if size > SOURCE_LIMIT {
+        return Err(format!(
+            "the code alone is {} characters, over the {} limit",
+            size, SOURCE_LIMIT
+        ));
+    }
+
+    let mut table = format!("{}{}", ATTRIBUTES, header);
+    let mut embedded = Vec::new();
+    let mut skipped = Vec::new();
+    for (fingerprint, actions) in solutions {
+        let line = solution_line(*fingerprint, actions)?;
+        if size + line.len() > SOURCE_LIMIT {
+            skipped.push(*fingerprint);
+            continue;
+        }
+        size += line.len();
+        table.push_str(&line);
+        embedded.push(*fingerprint);
+    }
+    table.push_str(footer);
+
+    Ok(Submission {
+        source: table + &code,
+        embedded,
+        skipped,
+    })
+}
+
+pub fn solutions_from_store<P: AsRef<Path>>(
+    store: &Store,
+    maps_dir: P,
+) -> Result<Vec<(u64, Vec<Action>)>, Error> {
+    let mut solutions = Vec::new();
+    for solution in store.entries()? {
+        let map = maps_dir.as_ref().join(format!("{}.json", solution.map));
+        if !map.is_file() {
+            continue;
+        }
+        let fingerprint = bot::fingerprint(&race::load_points(map)?);
+        let actions = solution.actions().map_err(|source| Error::Parse {
+            path: store.path(&solution.map),
+            source,
+        })?;
+        solutions.push((fingerprint, actions));
+    }
+    Ok(solutions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip() {
+        let content =
+            "use a;\n\n    // comment\n    let b = 1; // kept\n#[cfg(test)]\nmod tests {}\n";
+        assert_eq!(strip(content), "use a;\nlet b = 1; // kept");
+    }
+
+    #[test]
+    fn test_generate() {
+        let actions = vec![Action::new(200, 0); 20];
+        let submission = generate(&[(0x1234, actions)]).unwrap();
+
+        assert_eq!(submission.embedded, vec![0x1234]);
+        assert!(submission.skipped.is_empty());
+        assert!(submission.source.chars().count() <= SOURCE_LIMIT);
+        let packed = codec::encode(&[Action::new(200, 0); 20]).unwrap();
+        assert!(
+            submission
+                .source
+                .contains(&format!("(0x0000000000001234, \"{}\")", packed))
+        );
+        assert!(submission.source.contains("pub mod pod {"));
+        assert!(!submission.source.contains("#[cfg(test)]"));
+        assert!(!submission.source.contains("serde"));
+        assert!(!submission.source.contains("crate::race"));
+    }
+
+    #[test]
+    fn test_generate_respects_limit() {
+        let actions = [Action::new(200, 0), Action::new(100, 18)];
+        let long: Vec<Action> = actions.iter().cycle().take(5_000).copied().collect();
+        let solutions: Vec<(u64, Vec<Action>)> = (0..10).map(|i| (i, long.clone())).collect();
+
+        let submission = generate(&solutions).unwrap();
+        assert!(!submission.skipped.is_empty());
+        assert_eq!(
+            submission.embedded.len() + submission.skipped.len(),
+            solutions.len()
+        );
+        assert!(submission.source.chars().count() <= SOURCE_LIMIT);
+    }
+
+    #[test]
+    fn test_room_for_solutions() {
+        // Only the bot and the solvers it plays with are embedded, the table gets the rest
+        let submission = generate(&[]).unwrap();
+        assert!(SOURCE_LIMIT - submission.source.chars().count() >= 60_000);
+        assert!(submission.source.contains("pub mod cem {"));
+        assert!(!submission.source.contains("pub mod annealing"));
+        assert!(!submission.source.contains("planner_by_name"));
+    }
+
+    #[test]
+    fn test_generated_source_compiles() {
+        let dir =
+            std::env::temp_dir().join(format!("search_race_submission_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("main.rs");
+        let submission = generate(&[(0x1234, vec![Action::new(200, 0); 20])]).unwrap();
+        std::fs::write(&path, &submission.source).unwrap();
+
+        // A module missing from SOURCES or a newer syntax only shows up here
+        let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+        let output = std::process::Command::new(rustc)
+            .args(["--edition", "2021", "--emit=metadata", "--out-dir"])
+            .arg(&dir)
+            .arg(&path)
+            .output()
+            .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+}
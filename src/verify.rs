@@ -0,0 +1,134 @@
+use std::fmt;
+
+use crate::game::action::Action;
+use crate::race::Race;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Crossing {
+    pub checkpoint: usize,
+    pub turn: usize,
+    pub time: f64,
+}
+
+#[derive(Debug)]
+pub struct Report {
+    pub score: f64,
+    pub finished: bool,
+    pub turns: usize,
+    pub crossings: Vec<Crossing>,
+    pub invalid: Vec<(usize, Action)>,
+    pub ignored: usize,
+}
+
+pub fn verify(race: &Race, actions: &[Action]) -> Report {
+    let mut pod = race.initial_pod();
+    let mut crossings = Vec::new();
+    let mut played = 0;
+
+    for action in actions {
+        if pod.done {
+            break;
+        }
+
+        let turn = pod.turn;
+        let checkpoint = pod.next_checkpoint_id;
+        race.step(&mut pod, action);
+        played += 1;
+
+        if pod.next_checkpoint_id != checkpoint {
+            crossings.push(Crossing {
+                checkpoint,
+                turn,
+                time: pod.last_score - turn as f64,
+            });
+        }
+    }
+
+    // The referee would refuse these, the simulator plays them as is
+    let invalid = actions
+        .iter()
+        .enumerate()
+        .filter(|(_, action)| !race.track.rules.allows(action))
+        .map(|(turn, action)| (turn, *action))
+        .collect();
+
+    Report {
+        score: pod.last_score,
+        finished: race.is_finished(&pod),
+        turns: pod.turn,
+        crossings,
+        invalid,
+        ignored: actions.len() - played,
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Final Score: {}", self.score)?;
+        writeln!(f, "Finished: {}", self.finished)?;
+        writeln!(f, "Turns: {}", self.turns)?;
+
+        writeln!(f, "Checkpoints:")?;
+        for crossing in &self.crossings {
+            writeln!(
+                f,
+                "  #{:<3} turn {:>4} + {:.4}",
+                crossing.checkpoint, crossing.turn, crossing.time
+            )?;
+        }
+
+        if self.ignored > 0 {
+            writeln!(f, "Ignored: {} actions after the end", self.ignored)?;
+        }
+        for (turn, action) in &self.invalid {
+            writeln!(f, "Invalid action at turn {}: {}", turn, action)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::action::parse_actions;
+    use crate::search::{Budget, GreedySearch, Search};
+
+    #[test]
+    fn test_verify_finished_race() {
+        let race = Race::load("testcases/test1.json").unwrap();
+        let mut actions = GreedySearch::default()
+            .search(&race.track, &race.initial_pod(), &mut Budget::unlimited())
+            .actions;
+        actions.push(Action::new(200, 0));
+
+        let report = verify(&race, &actions);
+        assert!(report.finished);
+        assert_eq!(report.crossings.len(), race.track.total());
+        assert_eq!(report.ignored, 1);
+        assert!(report.invalid.is_empty());
+        assert_eq!(Some(report.score), race.score(&actions));
+
+        let last = report.crossings.last().unwrap();
+        assert_eq!(last.turn as f64 + last.time, report.score);
+        assert!(
+            report
+                .crossings
+                .windows(2)
+                .all(|w| w[0].checkpoint + 1 == w[1].checkpoint && w[0].turn <= w[1].turn)
+        );
+    }
+
+    #[test]
+    fn test_verify_invalid_actions() {
+        let race = Race::load("testcases/test1.json").unwrap();
+        let actions = parse_actions("200,0; 250,0;100,-30;0,18;").unwrap();
+
+        let report = verify(&race, &actions);
+        assert!(!report.finished);
+        assert_eq!(report.turns, 4);
+        assert_eq!(
+            report.invalid,
+            vec![(1, Action::new(250, 0)), (2, Action::new(100, -30))]
+        );
+    }
+}